@@ -0,0 +1,253 @@
+use crate::{find_attribute_args, has_flag};
+use syn::spanned::Spanned;
+use syn::{FnArg, ItemTrait, TraitItem, TraitItemMethod, TypeParamBound, WherePredicate};
+
+///Supertraits flapigen can add to the `self_type` of a callback
+pub const CALLBACK_SUPERTRAITS: [&str; 2] = ["Send", "Sync"];
+
+///Why a method of a callback trait is left out of the `foreign_callback!`
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SkipReason {
+    ///`#[generate_interface(skip)]`
+    Attribute,
+    ///the method has type or const parameters
+    Generic,
+    ///`where Self: Sized` so it can't be called on the callback
+    SizedSelf,
+}
+
+impl SkipReason {
+    ///Methods skipped because of the attribute aren't reported
+    pub fn is_automatic(self) -> bool {
+        self != SkipReason::Attribute
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            SkipReason::Attribute => "it's marked with #[generate_interface(skip)]",
+            SkipReason::Generic => "flapigen doesn't support generic callback methods",
+            SkipReason::SizedSelf => "it requires `Self: Sized`",
+        }
+    }
+}
+
+///Returns why the method shouldn't be exported, if it shouldn't
+pub fn skip_reason(method: &TraitItemMethod) -> Option<SkipReason> {
+    let skipped = find_attribute_args(&method.attrs, "generate_interface")
+        .map(|args| has_flag(&args, "skip"))
+        .unwrap_or_default();
+    let generics = &method.sig.generics;
+    let is_generic = generics
+        .params
+        .iter()
+        .any(|it| !matches!(it, syn::GenericParam::Lifetime(_)));
+    if skipped {
+        Some(SkipReason::Attribute)
+    } else if is_generic {
+        Some(SkipReason::Generic)
+    } else if requires_sized_self(method) {
+        Some(SkipReason::SizedSelf)
+    } else {
+        None
+    }
+}
+
+///`where Self: Sized`. Generic methods need it so the trait can still be used as `dyn Trait`
+pub fn requires_sized_self(method: &TraitItemMethod) -> bool {
+    method
+        .sig
+        .generics
+        .where_clause
+        .iter()
+        .flat_map(|it| it.predicates.iter())
+        .any(|it| match it {
+            WherePredicate::Type(predicate) => {
+                matches!(&predicate.bounded_ty, syn::Type::Path(ty) if ty.path.is_ident("Self"))
+                    && predicate
+                        .bounds
+                        .iter()
+                        .any(|bound| is_trait(bound, "Sized"))
+            }
+            _ => false,
+        })
+}
+
+///The supertraits of the callback which flapigen supports ie `Send` and `Sync`.
+/// Other supertraits are returned as errors since the foreign implementation can't implement them
+pub fn callback_supertraits(item: &ItemTrait) -> (Vec<String>, Vec<syn::Error>) {
+    let mut supertraits = vec![];
+    let mut errors = vec![];
+    for bound in &item.supertraits {
+        match bound {
+            TypeParamBound::Trait(val) => {
+                match val.path.segments.last().map(|it| it.ident.to_string()) {
+                    Some(name) if CALLBACK_SUPERTRAITS.contains(&name.as_str()) => {
+                        supertraits.push(name)
+                    }
+                    _ => errors.push(syn::Error::new(
+                        bound.span(),
+                        "foreign implementations of callbacks can only implement Send and Sync \
+                        besides the callback itself",
+                    )),
+                }
+            }
+            //'static
+            TypeParamBound::Lifetime(_) => {}
+        }
+    }
+    (supertraits, errors)
+}
+
+///Checks that flapigen can generate the callback ie no associated types or consts, generic
+/// parameters, async methods or methods taking `self` by value.
+/// The messages name the item since the generator reports them without spans
+pub fn callback_shape_errors(item: &ItemTrait) -> Vec<syn::Error> {
+    let name = &item.ident;
+    let mut errors = vec![];
+    if !item.generics.params.is_empty() {
+        errors.push(syn::Error::new_spanned(
+            &item.generics,
+            format!(
+                "generic callback `{}` isn't supported by flapigen. \
+                Use concrete types or a trait for each of them",
+                name
+            ),
+        ))
+    }
+    for it in &item.items {
+        match it {
+            TraitItem::Type(val) => errors.push(syn::Error::new_spanned(
+                val,
+                format!(
+                    "associated type `{}::{}` isn't supported in callbacks. \
+                    Replace it with a concrete type",
+                    name, val.ident
+                ),
+            )),
+            TraitItem::Const(val) => errors.push(syn::Error::new_spanned(
+                val,
+                format!(
+                    "associated const `{}::{}` isn't supported in callbacks. \
+                    Move it to an exported struct or pass it to the callback's methods",
+                    name, val.ident
+                ),
+            )),
+            //skipped methods aren't part of the callback
+            TraitItem::Method(method) if skip_reason(method).is_none() => {
+                let method_name = &method.sig.ident;
+                if let Some(token) = &method.sig.asyncness {
+                    errors.push(syn::Error::new_spanned(
+                        token,
+                        format!(
+                            "`{}::{}` can't be async since the foreign implementation is called \
+                            synchronously. Return the result through another callback instead",
+                            name, method_name
+                        ),
+                    ))
+                }
+                match method.sig.inputs.first() {
+                    Some(FnArg::Receiver(val)) if val.reference.is_some() => {}
+                    Some(receiver @ FnArg::Receiver(_)) => errors.push(syn::Error::new_spanned(
+                        receiver,
+                        format!(
+                            "`{}::{}` takes `self` by value but callbacks are called through \
+                            `&self` or `&mut self`. Change the receiver or skip the method with \
+                            `where Self: Sized` and a default implementation",
+                            name, method_name
+                        ),
+                    )),
+                    //ie `self: Box<Self>`
+                    Some(receiver @ FnArg::Typed(val)) if matches!(&*val.pat, syn::Pat::Ident(it) if it.ident == "self") => {
+                        errors.push(syn::Error::new_spanned(
+                            receiver,
+                            format!(
+                                "`{}::{}` should take `&self` or `&mut self` \
+                                to be called on the callback",
+                                name, method_name
+                            ),
+                        ))
+                    }
+                    _ => errors.push(syn::Error::new_spanned(
+                        &method.sig,
+                        format!(
+                            "`{}::{}` has no `&self` or `&mut self` receiver so it can't be \
+                            called on the callback. Add one or skip the method with \
+                            `where Self: Sized` and a default implementation",
+                            name, method_name
+                        ),
+                    )),
+                }
+            }
+            _ => {}
+        }
+    }
+    errors
+}
+
+fn is_trait(bound: &TypeParamBound, name: &str) -> bool {
+    matches!(bound, TypeParamBound::Trait(val) if val.path.segments.last().map(|it| it.ident == name).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::callbacks::{callback_shape_errors, callback_supertraits, skip_reason, SkipReason};
+
+    #[test]
+    fn callbacks() {
+        let item: syn::ItemTrait = syn::parse_str(
+            "trait Listener: Send + std::marker::Sync + Debug + 'static {
+                fn on_event(&self, id: i32);
+                #[generate_interface(skip)]
+                fn describe(&self) -> String { String::new() }
+                fn map<T>(&self, value: T) -> T { value }
+                fn boxed(self) -> Box<Self> where Self: Sized { Box::new(self) }
+                fn borrowed<'a>(&self, value: &'a str) {}
+            }",
+        )
+        .unwrap();
+        let reasons = item
+            .items
+            .iter()
+            .filter_map(|it| match it {
+                syn::TraitItem::Method(method) => Some(skip_reason(method)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reasons,
+            vec![
+                None,
+                Some(SkipReason::Attribute),
+                Some(SkipReason::Generic),
+                Some(SkipReason::SizedSelf),
+                None
+            ]
+        );
+        let (supertraits, errors) = callback_supertraits(&item);
+        assert_eq!(supertraits, vec!["Send", "Sync"]);
+        assert_eq!(errors.len(), 1);
+    }
+    #[test]
+    fn callback_shape() {
+        let item: syn::ItemTrait = syn::parse_str(
+            "trait Listener<T> {
+                type Output;
+                const ID: i32;
+                async fn fetch(&self);
+                fn consume(self);
+                fn boxed(self: Box<Self>);
+                fn create() -> i32;
+                fn create_default() -> i32 where Self: Sized { 0 }
+                fn on_event(&mut self, id: i32);
+            }",
+        )
+        .unwrap();
+        let errors = callback_shape_errors(&item)
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(errors.len(), 7);
+        assert!(errors[1].contains("`Listener::Output`"));
+        assert!(errors[6].contains("`Listener::create`"));
+    }
+}
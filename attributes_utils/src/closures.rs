@@ -0,0 +1,140 @@
+use syn::{
+    GenericArgument, ParenthesizedGenericArguments, PathArguments, ReturnType, Type, TypeParamBound,
+};
+
+///How a closure is passed to a method
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ClosureKind {
+    ///`impl Fn(i32) -> bool`
+    Impl,
+    ///`Box<dyn Fn(String)>`
+    Boxed,
+    ///`&dyn FnMut(Event)`
+    Ref,
+    ///`&mut dyn FnMut(Event)`
+    RefMut,
+}
+
+///A closure argument. Foreign code passes a callback object which is turned into the closure
+#[derive(Debug)]
+pub struct ClosureType<'a> {
+    pub kind: ClosureKind,
+    pub inputs: Vec<&'a Type>,
+    pub output: Option<&'a Type>,
+    ///Whether it should be `Sync`, which callbacks aren't
+    pub is_sync: bool,
+}
+
+///The closure passed as `ty`, if it's one
+pub fn closure_type(ty: &Type) -> Option<ClosureType<'_>> {
+    match ty {
+        Type::ImplTrait(val) => from_bounds(ClosureKind::Impl, val.bounds.iter()),
+        Type::Reference(val) => match &*val.elem {
+            Type::TraitObject(object) => {
+                let kind = match val.mutability {
+                    Some(_) => ClosureKind::RefMut,
+                    None => ClosureKind::Ref,
+                };
+                from_bounds(kind, object.bounds.iter())
+            }
+            _ => None,
+        },
+        Type::Path(val) if val.qself.is_none() => {
+            let segment = val.path.segments.last()?;
+            match &segment.arguments {
+                PathArguments::AngleBracketed(args) if segment.ident == "Box" => {
+                    match args.args.iter().collect::<Vec<_>>()[..] {
+                        [GenericArgument::Type(Type::TraitObject(object))] => {
+                            from_bounds(ClosureKind::Boxed, object.bounds.iter())
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn from_bounds<'a, I: Iterator<Item = &'a TypeParamBound>>(
+    kind: ClosureKind,
+    bounds: I,
+) -> Option<ClosureType<'a>> {
+    let mut arguments: Option<&ParenthesizedGenericArguments> = None;
+    let mut is_sync = false;
+    for bound in bounds {
+        let path = match bound {
+            TypeParamBound::Trait(val) => &val.path,
+            TypeParamBound::Lifetime(_) => continue,
+        };
+        let segment = path.segments.last()?;
+        match (&segment.arguments, segment.ident.to_string().as_str()) {
+            (PathArguments::Parenthesized(val), "Fn" | "FnMut" | "FnOnce") => arguments = Some(val),
+            (PathArguments::None, "Sync") => is_sync = true,
+            (PathArguments::None, "Send") => {}
+            _ => return None,
+        }
+    }
+    let arguments = arguments?;
+    Some(ClosureType {
+        kind,
+        inputs: arguments.inputs.iter().collect(),
+        output: match &arguments.output {
+            ReturnType::Type(_, ty) if !is_unit(ty) => Some(&**ty),
+            _ => None,
+        },
+        is_sync,
+    })
+}
+
+fn is_unit(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(val) if val.elems.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::closures::{closure_type, ClosureKind};
+    use quote::ToTokens;
+
+    #[test]
+    fn closures() {
+        let closure = |it: &str| {
+            closure_type(&syn::parse_str(it).unwrap()).map(|it| {
+                (
+                    it.kind,
+                    it.inputs
+                        .iter()
+                        .map(|it| it.to_token_stream().to_string())
+                        .collect::<Vec<_>>(),
+                    it.output.map(|it| it.to_token_stream().to_string()),
+                    it.is_sync,
+                )
+            })
+        };
+        assert_eq!(
+            closure("impl Fn(i32) -> bool"),
+            Some((
+                ClosureKind::Impl,
+                vec![String::from("i32")],
+                Some(String::from("bool")),
+                false
+            ))
+        );
+        assert_eq!(
+            closure("Box<dyn Fn(String) + Send + Sync + 'static>"),
+            Some((ClosureKind::Boxed, vec![String::from("String")], None, true))
+        );
+        assert_eq!(
+            closure("&mut dyn FnMut(&str, u8) -> ()"),
+            Some((
+                ClosureKind::RefMut,
+                vec![String::from("& str"), String::from("u8")],
+                None,
+                false
+            ))
+        );
+        assert!(closure("Box<dyn Listener>").is_none());
+        assert!(closure("impl Fn(i32) + Clone").is_none());
+    }
+}
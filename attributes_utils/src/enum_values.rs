@@ -0,0 +1,190 @@
+use crate::ffi_types::FfiLanguage;
+use syn::spanned::Spanned;
+use syn::{Expr, ItemEnum, Lit, NestedMeta, UnOp};
+
+///Integer types of `#[repr]`
+const REPR_TYPES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+///Values of the variants of an exported enum, passed to foreign code as `value_type`
+#[derive(Debug, PartialEq)]
+pub struct EnumValues {
+    ///The integer type of the `#[repr]` or `i32` without one
+    pub value_type: &'static str,
+    ///`None` for discriminants which aren't integer literals so they can't be checked
+    pub values: Vec<Option<i128>>,
+}
+
+///The values of an enum with discriminants or an integer `#[repr]`, checking they fit the
+/// integer type in `language`. `None` for other enums, which are exported without values
+pub fn enum_values(item: &ItemEnum, language: FfiLanguage) -> Option<syn::Result<EnumValues>> {
+    let repr = repr_type(item);
+    if repr.is_none() && item.variants.iter().all(|it| it.discriminant.is_none()) {
+        return None;
+    }
+    let value_type = match value_type(repr.as_deref(), language) {
+        Some(val) => val,
+        None => {
+            return Some(Err(syn::Error::new(
+                item.ident.span(),
+                format!(
+                    "flapigen can't pass {} values to {}",
+                    repr.unwrap_or_default(),
+                    language.name()
+                ),
+            )))
+        }
+    };
+    let (min, max) = range(value_type, language);
+    let mut values = vec![];
+    let mut errors: Option<syn::Error> = None;
+    //implicit discriminants are one more than the previous one
+    let mut next = Some(0);
+    for variant in &item.variants {
+        let value = match &variant.discriminant {
+            Some((_, expr)) => evaluate(expr),
+            None => next,
+        };
+        if let Some(value) = value.filter(|it| *it < min || *it > max) {
+            let error = syn::Error::new(
+                variant
+                    .discriminant
+                    .as_ref()
+                    .map_or(variant.span(), |it| it.1.span()),
+                format!(
+                    "{} doesn't fit in {} in {}{}",
+                    value,
+                    value_type,
+                    language.name(),
+                    if repr.is_none() {
+                        ". Set the type of the values with #[repr]"
+                    } else {
+                        ""
+                    }
+                ),
+            );
+            match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            }
+        }
+        next = value.map(|it| it + 1);
+        values.push(value)
+    }
+    Some(match errors {
+        Some(errors) => Err(errors),
+        None => Ok(EnumValues { value_type, values }),
+    })
+}
+
+///The integer type of `#[repr(u8)]` or `#[repr(C, u8)]`
+fn repr_type(item: &ItemEnum) -> Option<String> {
+    item.attrs
+        .iter()
+        .filter(|it| it.path.is_ident("repr"))
+        .flat_map(crate::attribute_args)
+        .find_map(|it| match it {
+            NestedMeta::Meta(syn::Meta::Path(path)) => path
+                .get_ident()
+                .map(|it| it.to_string())
+                .filter(|it| REPR_TYPES.contains(&it.as_str())),
+            _ => None,
+        })
+}
+
+///The type passed to foreign code. It's `i32` for enums without an integer `#[repr]` like C enums
+fn value_type(repr: Option<&str>, language: FfiLanguage) -> Option<&'static str> {
+    match (repr, language) {
+        (None, _) => Some("i32"),
+        (Some("i128" | "u128"), _) => None,
+        //flapigen doesn't convert isize to Java
+        (Some("isize"), FfiLanguage::Java) => Some("i64"),
+        (Some(repr), _) => REPR_TYPES.iter().find(|it| **it == repr).copied(),
+    }
+}
+
+///The values held by `value_type` both in Rust and in `language`
+fn range(value_type: &str, language: FfiLanguage) -> (i128, i128) {
+    match value_type {
+        "i8" => (i8::MIN.into(), i8::MAX.into()),
+        "i16" => (i16::MIN.into(), i16::MAX.into()),
+        "i32" => (i32::MIN.into(), i32::MAX.into()),
+        "u8" => (0, u8::MAX.into()),
+        "u16" => (0, u16::MAX.into()),
+        "u32" => (0, u32::MAX.into()),
+        //Java has no unsigned long
+        "u64" | "usize" if language == FfiLanguage::Java => (0, i64::MAX.into()),
+        "u64" | "usize" => (0, u64::MAX.into()),
+        _ => (i64::MIN.into(), i64::MAX.into()),
+    }
+}
+
+///The value of integer literals like `404`, `-1` or `0x10`
+fn evaluate(expr: &Expr) -> Option<i128> {
+    match expr {
+        Expr::Lit(val) => match &val.lit {
+            Lit::Int(int) => int.base10_parse().ok(),
+            _ => None,
+        },
+        Expr::Unary(val) if matches!(val.op, UnOp::Neg(_)) => evaluate(&val.expr).map(|it| -it),
+        Expr::Paren(val) => evaluate(&val.expr),
+        Expr::Group(val) => evaluate(&val.expr),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enum_values::{enum_values, EnumValues};
+    use crate::ffi_types::FfiLanguage;
+
+    #[test]
+    fn values() {
+        let values = |it: &str, language: FfiLanguage| {
+            enum_values(&syn::parse_str(it).unwrap(), language).map(|it| it.map_err(|_| ()))
+        };
+        assert_eq!(
+            values(
+                "#[repr(u16)] enum Status { Ok = 200, Created, NotFound = 0x194, Other = BASE }",
+                FfiLanguage::Java
+            ),
+            Some(Ok(EnumValues {
+                value_type: "u16",
+                values: vec![Some(200), Some(201), Some(404), None]
+            }))
+        );
+        assert_eq!(
+            values(
+                "#[repr(C, isize)] enum Level { Low = -1, High }",
+                FfiLanguage::Java
+            ),
+            Some(Ok(EnumValues {
+                value_type: "i64",
+                values: vec![Some(-1), Some(0)]
+            }))
+        );
+        assert_eq!(values("enum Color { Red, Green }", FfiLanguage::Java), None);
+        assert_eq!(
+            values("enum Big { Max = 4294967296 }", FfiLanguage::Cpp),
+            Some(Err(()))
+        );
+        assert_eq!(
+            values(
+                "#[repr(u64)] enum Mask { All = 0xFFFFFFFFFFFFFFFF }",
+                FfiLanguage::Java
+            ),
+            Some(Err(()))
+        );
+        assert!(values(
+            "#[repr(u64)] enum Mask { All = 0xFFFFFFFFFFFFFFFF }",
+            FfiLanguage::Cpp
+        )
+        .unwrap()
+        .is_ok());
+        assert_eq!(
+            values("#[repr(u128)] enum Id { A = 1 }", FfiLanguage::Cpp),
+            Some(Err(()))
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use crate::closures::closure_type;
+use crate::COPY_TYPES;
+use syn::spanned::Spanned;
+use syn::{FnArg, ReturnType, Signature, Type};
+
+///Languages whose types can be checked at compile time
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum FfiLanguage {
+    Java,
+    Cpp,
+}
+
+impl FfiLanguage {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            FfiLanguage::Java => "Java",
+            FfiLanguage::Cpp => "C++",
+        }
+    }
+
+    ///Types converted by flapigen without a custom typemap
+    fn primitives(self) -> &'static [&'static str] {
+        match self {
+            FfiLanguage::Java => &[
+                "bool", "i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "f32", "f64", "usize",
+            ],
+            FfiLanguage::Cpp => &[
+                "bool", "i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "f32", "f64",
+                "usize", "isize",
+            ],
+        }
+    }
+
+    ///Whether `Vec<T>` and `&[T]` of `element` are converted to arrays in all the languages
+    pub(crate) fn is_array_element(element: &str) -> bool {
+        [FfiLanguage::Java, FfiLanguage::Cpp]
+            .iter()
+            .all(|it| it.array_elements().contains(&element))
+    }
+
+    ///Element types of `Vec<T>` and `&[T]` which are converted to arrays
+    fn array_elements(self) -> &'static [&'static str] {
+        match self {
+            FfiLanguage::Java => &["i8", "i16", "i32", "i64", "f32", "f64", "String"],
+            FfiLanguage::Cpp => &[
+                "i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "f32", "f64", "usize",
+                "isize",
+            ],
+        }
+    }
+}
+
+///Result of checking the types of a method against what flapigen supports
+#[derive(Default)]
+pub struct TypeCheck {
+    pub errors: Vec<syn::Error>,
+    ///Types which aren't built in so they should be exported classes, enums or callbacks.
+    /// They're checked with a marker trait since the macro can't tell
+    pub exported: Vec<Type>,
+    ///Error types of `Result`s which should be mapped to Java exceptions
+    pub exceptions: Vec<Type>,
+}
+
+impl TypeCheck {
+    ///Checks the types of the arguments and return type of `signature`
+    pub fn signature(signature: &Signature, language: FfiLanguage) -> TypeCheck {
+        let mut check = TypeCheck::default();
+        for input in &signature.inputs {
+            if let FnArg::Typed(arg) = input {
+                check.check(&arg.ty, language)
+            }
+        }
+        if let ReturnType::Type(_, ty) = &signature.output {
+            match generic_arguments(ty).as_slice() {
+                //the errors of async methods are passed to the completion callback as messages
+                [ok, ..]
+                    if signature.asyncness.is_some()
+                        && type_name(ty).as_deref() == Some("Result") =>
+                {
+                    check.check(ok, language)
+                }
+                _ => check.check(ty, language),
+            }
+        }
+        check
+    }
+
+    fn check(&mut self, ty: &Type, language: FfiLanguage) {
+        //foreign callbacks are turned into the closure so its types should be supported
+        if let Some(closure) = closure_type(ty) {
+            if closure.is_sync {
+                self.errors.push(syn::Error::new_spanned(
+                    ty,
+                    "closures passed by foreign code can't be Sync since callbacks aren't",
+                ))
+            }
+            closure
+                .inputs
+                .into_iter()
+                .chain(closure.output)
+                .for_each(|it| self.check(it, language));
+            return;
+        }
+        match ty {
+            Type::Paren(val) => self.check(&val.elem, language),
+            Type::Group(val) => self.check(&val.elem, language),
+            //()
+            Type::Tuple(val) if val.elems.is_empty() => {}
+            Type::Reference(val) => match &*val.elem {
+                Type::Path(path) if path.path.is_ident("str") => {}
+                Type::Slice(slice) => self.check_element(ty, &slice.elem, language),
+                Type::TraitObject(_) => self.exported(&val.elem),
+                elem if is_plain(elem) => self.exported(elem),
+                _ => self.unsupported(ty, language),
+            },
+            Type::Path(val) if val.qself.is_none() => {
+                let name = match val.path.segments.last() {
+                    Some(segment) => segment.ident.to_string(),
+                    None => return,
+                };
+                let args = generic_arguments(ty);
+                match (name.as_str(), args.as_slice()) {
+                    ("Self" | "String", []) => {}
+                    (name, []) if language.primitives().contains(&name) => {}
+                    (name, []) if COPY_TYPES.contains(&name) => self.unsupported(ty, language),
+                    //exported class or enum
+                    (_, []) => self.exported(ty),
+                    ("Vec", [element]) => self.check_element(ty, element, language),
+                    ("Option", [inner]) => self.check_option(ty, inner, language),
+                    ("Result", [ok, error]) => {
+                        self.check(ok, language);
+                        self.check_error(error, language)
+                    }
+                    //callbacks
+                    ("Box", [inner @ Type::TraitObject(_)]) => self.exported(inner),
+                    ("Arc", [inner]) if type_name(inner).as_deref() == Some("Mutex") => {
+                        self.check_shared(ty, inner, language)
+                    }
+                    ("Rc", [inner]) if type_name(inner).as_deref() == Some("RefCell") => {
+                        self.check_shared(ty, inner, language)
+                    }
+                    _ => self.unsupported(ty, language),
+                }
+            }
+            _ => self.unsupported(ty, language),
+        }
+    }
+
+    ///Elements of `Vec<T>` and `&[T]`
+    fn check_element(&mut self, ty: &Type, element: &Type, language: FfiLanguage) {
+        match type_name(element) {
+            Some(name) if language.array_elements().contains(&name.as_str()) => {}
+            Some(name) if !language.primitives().contains(&name.as_str()) && is_plain(element) => {
+                self.exported(element)
+            }
+            _ => self.unsupported(ty, language),
+        }
+    }
+
+    fn check_option(&mut self, ty: &Type, inner: &Type, language: FfiLanguage) {
+        if language == FfiLanguage::Cpp {
+            return self.check(inner, language);
+        }
+        let inner = match inner {
+            Type::Reference(val) if val.mutability.is_none() => &*val.elem,
+            _ => inner,
+        };
+        match type_name(inner).as_deref() {
+            Some("i8" | "i16" | "i32" | "i64" | "f32" | "f64" | "String" | "str") => {}
+            Some(name) if !language.primitives().contains(&name) && is_plain(inner) => {
+                self.exported(inner)
+            }
+            _ => self.unsupported(ty, language),
+        }
+    }
+
+    fn check_error(&mut self, error: &Type, language: FfiLanguage) {
+        if language == FfiLanguage::Cpp {
+            return self.check(error, language);
+        }
+        match error {
+            Type::Path(val) if val.path.is_ident("String") => {}
+            Type::Reference(val) if matches!(&*val.elem, Type::Path(it) if it.path.is_ident("str")) => {}
+            //checked with a marker trait
+            error if is_plain(error) => self.exceptions.push(error.clone()),
+            _ => self.errors.push(syn::Error::new(
+                error.span(),
+                "flapigen only converts `Result<T, String>` and `Result<T, &str>` to exceptions in \
+                Java. Map the error to a String, use an error type annotated with \
+                #[generate_interface(exception = \"...\")] or add a typemap for the Result with \
+                foreign_typemap! and skip the check with #[generate_interface(unchecked)]",
+            )),
+        }
+    }
+
+    ///`Arc<Mutex<T>>` and `Rc<RefCell<T>>`
+    fn check_shared(&mut self, ty: &Type, inner: &Type, language: FfiLanguage) {
+        match generic_arguments(inner).as_slice() {
+            [class] if is_plain(class) => self.exported(class),
+            _ => self.unsupported(ty, language),
+        }
+    }
+
+    fn exported(&mut self, ty: &Type) {
+        let ty = match ty {
+            //ie `dyn Callback + Send` is checked as `dyn Callback`
+            Type::TraitObject(val) => match val.bounds.iter().find_map(|it| match it {
+                syn::TypeParamBound::Trait(bound) => Some(&bound.path),
+                _ => None,
+            }) {
+                Some(path) => syn::parse_quote_spanned! { ty.span() => dyn #path },
+                None => return,
+            },
+            //the exported class itself
+            Type::Path(val) if val.path.is_ident("Self") => return,
+            _ => ty.clone(),
+        };
+        self.exported.push(ty)
+    }
+
+    fn unsupported(&mut self, ty: &Type, language: FfiLanguage) {
+        self.errors.push(syn::Error::new(
+            ty.span(),
+            format!(
+                "flapigen can't convert this type to {} by default. Add a typemap for it with \
+                foreign_typemap! and skip the check with #[generate_interface(unchecked)], \
+                or wrap it in a struct exported with #[generate_interface_doc]",
+                language.name()
+            ),
+        ))
+    }
+}
+
+///A path without generic arguments ie `Foo` or `crate::Foo`
+fn is_plain(ty: &Type) -> bool {
+    match ty {
+        Type::Path(val) => {
+            val.qself.is_none() && val.path.segments.iter().all(|it| it.arguments.is_empty())
+        }
+        _ => false,
+    }
+}
+
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(val) if val.qself.is_none() => {
+            val.path.segments.last().map(|it| it.ident.to_string())
+        }
+        Type::Reference(val) => match &*val.elem {
+            Type::Path(path) if path.path.is_ident("str") => Some(String::from("str")),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn generic_arguments(ty: &Type) -> Vec<&Type> {
+    let segment = match ty {
+        Type::Path(val) => val.path.segments.last(),
+        _ => None,
+    };
+    match segment.map(|it| &it.arguments) {
+        Some(syn::PathArguments::AngleBracketed(args)) => args
+            .args
+            .iter()
+            .filter_map(|it| match it {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ffi_types::{FfiLanguage, TypeCheck};
+    use quote::ToTokens;
+
+    fn check(signature: &str, language: FfiLanguage) -> (usize, Vec<String>) {
+        let signature: syn::Signature = syn::parse_str(signature).unwrap();
+        let check = TypeCheck::signature(&signature, language);
+        let exported = check
+            .exported
+            .iter()
+            .chain(check.exceptions.iter())
+            .map(|it| it.to_token_stream().to_string())
+            .collect();
+        (check.errors.len(), exported)
+    }
+
+    #[test]
+    fn supported_types() {
+        let (errors, exported) = check(
+            "fn f(&self, a: i32, b: &str, c: String, d: &[i64], e: Option<f64>, f: &Foo, \
+            g: Box<dyn Callback + Send>, h: Vec<Bar>) -> Result<Option<Baz>, String>",
+            FfiLanguage::Java,
+        );
+        assert_eq!(errors, 0);
+        assert_eq!(exported, vec!["Foo", "dyn Callback", "Bar", "Baz"]);
+        let (errors, _) = check(
+            "fn f(a: isize, b: Vec<u8>, c: Option<bool>) -> Result<(), Error>",
+            FfiLanguage::Cpp,
+        );
+        assert_eq!(errors, 0);
+        let (errors, exported) = check(
+            "async fn f(&self) -> Result<Foo, Box<dyn Error>>",
+            FfiLanguage::Java,
+        );
+        assert_eq!(errors, 0);
+        assert_eq!(exported, vec!["Foo"]);
+        let (errors, exported) = check(
+            "fn f(a: impl Fn(i32) -> bool, b: Box<dyn Fn(String) + Send>, c: &dyn FnMut(Event))",
+            FfiLanguage::Java,
+        );
+        assert_eq!(errors, 0);
+        assert_eq!(exported, vec!["Event"]);
+    }
+
+    #[test]
+    fn unsupported_types() {
+        let (errors, exported) = check(
+            "fn f(a: isize, b: Vec<u8>, c: Option<bool>, d: (i32, i32), e: HashMap<i32, Foo>) \
+            -> Result<(), Box<dyn Error>>",
+            FfiLanguage::Java,
+        );
+        assert_eq!(errors, 6);
+        assert!(exported.is_empty());
+        let (errors, exported) = check("fn f() -> Result<i32, NetworkError>", FfiLanguage::Java);
+        assert_eq!(errors, 0);
+        assert_eq!(exported, vec!["NetworkError"]);
+    }
+}
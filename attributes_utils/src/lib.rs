@@ -1,56 +1,1184 @@
-use itertools::MultiUnzip;
-use quote::format_ident;
-use syn::ItemImpl;
+pub mod callbacks;
+pub mod closures;
+pub mod enum_values;
+pub mod ffi_types;
 
-pub fn generate_impl_block(item: &syn::ItemStruct) -> ItemImpl {
-    let name = item.clone().ident;
-    let vis = item.clone().vis;
-    let fields = match item.clone().fields {
-        syn::Fields::Named(fields) => fields.named.into_iter().collect::<Vec<_>>(),
-        _ => unreachable!(),
-    };
+use crate::ffi_types::FfiLanguage;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{ItemImpl, Lit, Meta, MetaNameValue, NestedMeta};
+
+///Values of `shared` in `#[generate_interface_doc(shared = "...")]`
+pub const SHARED_OWNERSHIP: [&str; 2] = ["arc_mutex", "rc_refcell"];
+
+///Name of the attribute used on fields of structs annotated with `#[generate_access_methods]`
+pub const FIELD_ATTRIBUTE: &str = "rifgen";
+
+///Primitive types which are `Copy` and so are returned by value
+const COPY_TYPES: [&str; 16] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize", "f32",
+    "f64", "bool", "char",
+];
+
+///Common types which aren't `Copy` so they can't have copying getters
+const NOT_COPY_TYPES: [&str; 11] = [
+    "String", "Vec", "HashMap", "HashSet", "BTreeMap", "BTreeSet", "VecDeque", "Box", "Rc", "Arc",
+    "str",
+];
+
+///Nouns ending with `s` which are the same in the singular
+const UNCOUNTABLE_NOUNS: [&str; 5] = ["news", "series", "species", "means", "physics"];
+
+///Plurals which the suffix rules of [`singular`] get wrong ie `movies` isn't `movy`
+const IRREGULAR_PLURALS: [(&str, &str); 8] = [
+    ("movies", "movie"),
+    ("cookies", "cookie"),
+    ("ties", "tie"),
+    ("pies", "pie"),
+    ("buses", "bus"),
+    ("statuses", "status"),
+    ("aliases", "alias"),
+    ("analyses", "analysis"),
+];
+
+///How getters return the value of a field
+#[derive(Copy, Clone, PartialEq)]
+enum GetterKind {
+    ///By value ie `self.x`. For `Copy` types
+    Copy,
+    ///By reference ie `&self.x`. `String` is returned as `&str` and `Vec<T>` as `&[T]`
+    Borrow,
+    ///`self.x.clone()`
+    Clone,
+}
+
+impl GetterKind {
+    fn from_attribute(value: &str) -> Option<GetterKind> {
+        match value {
+            "copy" => Some(GetterKind::Copy),
+            "borrow" => Some(GetterKind::Borrow),
+            "clone" => Some(GetterKind::Clone),
+            _ => None,
+        }
+    }
+
+    ///`Copy` for primitives, `Borrow` for `String` and `Vec` of the primitives which flapigen
+    /// converts from slices in all the languages ie `Vec<i32>` and `Clone` for the rest
+    fn infer(ty: &syn::Type) -> GetterKind {
+        let is_copy = |ty: &syn::Type| {
+            type_name(ty)
+                .map(|it| COPY_TYPES.contains(&it.as_str()))
+                .unwrap_or_default()
+        };
+        if is_copy(ty) {
+            return GetterKind::Copy;
+        }
+        let is_array_element = |ty: &syn::Type| {
+            is_copy(ty)
+                && type_name(ty)
+                    .map(|it| FfiLanguage::is_array_element(&it))
+                    .unwrap_or_default()
+        };
+        match type_name(ty).as_deref() {
+            Some("String") => GetterKind::Borrow,
+            Some("Vec")
+                if generic_argument(ty)
+                    .map(is_array_element)
+                    .unwrap_or_default() =>
+            {
+                GetterKind::Borrow
+            }
+            _ => GetterKind::Clone,
+        }
+    }
+
+    ///Copying getters of types which are never `Copy`, like `String`, would move the field
+    fn check(self, ty: &syn::Type) -> syn::Result<()> {
+        let not_copy = match ty {
+            syn::Type::Reference(val) => val.mutability.is_some(),
+            syn::Type::Slice(_) | syn::Type::TraitObject(_) => true,
+            _ => type_name(ty)
+                .map(|it| NOT_COPY_TYPES.contains(&it.as_str()))
+                .unwrap_or_default(),
+        };
+        if self == GetterKind::Copy && not_copy {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "copy getters need a Copy type. Use borrow or clone instead",
+            ));
+        }
+        Ok(())
+    }
+}
+
+///Options of `#[generate_access_methods(...)]`
+#[derive(Default)]
+pub struct AccessOptions {
+    ///Getter kind of all the fields. Inferred from the type of the field if it isn't set
+    getters: Option<GetterKind>,
+    ///Generate a builder ie `FooBuilder` for `Foo`
+    pub builder: bool,
+    ///Generate collection accessors for all the `Vec`, `HashMap` and `Option` fields
+    collections: bool,
+    ///Name the getters of `bool` fields `is_x` instead of `get_x`. Set with `getters_style = "is"`
+    is_getters: bool,
+}
 
-    let (f_setter, f_getter, f_ident, f_vis, f_ty): (Vec<_>, Vec<_>, Vec<_>, Vec<_>, Vec<_>) =
-        fields
+impl AccessOptions {
+    pub fn from_args(args: &[NestedMeta]) -> syn::Result<AccessOptions> {
+        let mut options = AccessOptions::default();
+        let mut errors = vec![];
+        for arg in args {
+            let arg = std::slice::from_ref(arg);
+            if has_flag(arg, "builder") {
+                options.builder = true;
+                continue;
+            }
+            if has_flag(arg, "collections") {
+                options.collections = true;
+                continue;
+            }
+            if let Some(style) = string_arg(arg, "getters_style") {
+                match style.as_str() {
+                    "get" => options.is_getters = false,
+                    "is" => options.is_getters = true,
+                    _ => errors.push(syn::Error::new_spanned(
+                        &arg[0],
+                        "getters_style should be either get or is",
+                    )),
+                }
+                continue;
+            }
+            match string_arg(arg, "getters") {
+                Some(val) if val == "auto" => options.getters = None,
+                Some(val) => match GetterKind::from_attribute(&val) {
+                    Some(kind) => options.getters = Some(kind),
+                    None => errors.push(syn::Error::new_spanned(
+                        &arg[0],
+                        "getters should be one of auto, copy, borrow or clone",
+                    )),
+                },
+                None => errors.push(syn::Error::new_spanned(
+                    &arg[0],
+                    "only getters, getters_style, builder and collections attributes are supported \
+                    for now",
+                )),
+            }
+        }
+        combine_errors(errors).map(|_| options)
+    }
+}
+
+///How the accessors of a field are generated. Set with `#[rifgen(...)]` on the field
+#[derive(Default)]
+struct FieldOptions {
+    ///no accessors and not a parameter of the constructor
+    skip: bool,
+    ///no setter
+    readonly: bool,
+    ///not a parameter of the constructor
+    no_constructor: bool,
+    getter: Option<syn::Ident>,
+    setter: Option<syn::Ident>,
+    getter_kind: Option<GetterKind>,
+    ///generate collection accessors. The attribute is kept to report errors
+    collection: Option<NestedMeta>,
+    ///name of an element of the collection used in the collection accessors
+    item: Option<syn::Ident>,
+}
+
+impl FieldOptions {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<FieldOptions> {
+        let mut options = FieldOptions::default();
+        let mut errors = vec![];
+        let args = attrs
             .iter()
-            .cloned()
-            .filter_map(|f| {
-                if let Some(ident) = f.ident {
-                    Some((
-                        format_ident!("set_{}", ident),
-                        format_ident!("get_{}", ident),
-                        ident,
-                        f.vis,
-                        f.ty,
-                    ))
-                } else {
-                    None
+            .filter(|attr| attr.path.is_ident(FIELD_ATTRIBUTE))
+            .flat_map(attribute_args);
+        for arg in args {
+            let arg = std::slice::from_ref(&arg);
+            let result = if has_flag(arg, "skip") {
+                options.skip = true;
+                Ok(())
+            } else if has_flag(arg, "readonly") {
+                options.readonly = true;
+                Ok(())
+            } else if has_flag(arg, "no_constructor") {
+                options.no_constructor = true;
+                Ok(())
+            } else if let Some(getter) = string_arg(arg, "getter") {
+                ident_arg(&arg[0], &getter).map(|it| options.getter = Some(it))
+            } else if let Some(setter) = string_arg(arg, "setter") {
+                ident_arg(&arg[0], &setter).map(|it| options.setter = Some(it))
+            } else if has_flag(arg, "collection") {
+                options.collection = Some(arg[0].clone());
+                Ok(())
+            } else if let Some(item) = string_arg(arg, "item") {
+                ident_arg(&arg[0], &item).map(|it| options.item = Some(it))
+            } else if let Some(kind) = string_arg(arg, "getter_kind") {
+                match GetterKind::from_attribute(&kind) {
+                    Some(kind) => {
+                        options.getter_kind = Some(kind);
+                        Ok(())
+                    }
+                    None => Err(syn::Error::new_spanned(
+                        &arg[0],
+                        "getter_kind should be one of copy, borrow or clone",
+                    )),
                 }
+            } else {
+                Err(syn::Error::new_spanned(
+                    &arg[0],
+                    "Unsupported field attribute. Expected one of skip, readonly, no_constructor, \
+                collection, getter = \"...\", setter = \"...\", getter_kind = \"...\" \
+                or item = \"...\"",
+                ))
+            };
+            if let Err(error) = result {
+                errors.push(error)
+            }
+        }
+        combine_errors(errors).map(|_| options)
+    }
+}
+
+///Parses the value of an argument such as `getter = "name"` as an identifier
+fn ident_arg(arg: &NestedMeta, value: &str) -> syn::Result<syn::Ident> {
+    syn::parse_str(value)
+        .map_err(|_| syn::Error::new_spanned(arg, format!("`{}` isn't a valid identifier", value)))
+}
+
+///Removes the `#[rifgen(...)]` attributes from the fields since they're only meant for
+/// [`generate_impl_block`] and rustc doesn't know them
+pub fn strip_field_attributes(item: &mut syn::ItemStruct) {
+    item.fields.iter_mut().for_each(|field| {
+        field
+            .attrs
+            .retain(|attr| !attr.path.is_ident(FIELD_ATTRIBUTE))
+    })
+}
+
+fn is_bool(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(val) if val.qself.is_none() && val.path.is_ident("bool"))
+}
+
+///Text of the doc comments ie ` Timeout in milliseconds` for `/// Timeout in milliseconds`
+fn doc_lines(attrs: &[syn::Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(MetaNameValue {
+                lit: Lit::Str(val), ..
+            })) => Some(val.value()),
+            _ => None,
+        })
+        .collect()
+}
+
+///Prefixes the first line of the docs of a field with a verb.
+/// ie `Timeout in milliseconds` becomes `Returns timeout in milliseconds`
+fn accessor_docs(verb: &str, docs: &[String]) -> Vec<String> {
+    let mut result = docs.to_vec();
+    if let Some(first) = result.first_mut() {
+        let line = first.trim_start();
+        let mut chars = line.chars();
+        //keep acronyms as they are
+        let line = match (chars.next(), chars.next()) {
+            (Some(first), Some(second)) if !second.is_uppercase() => first
+                .to_lowercase()
+                .chain(line[first.len_utf8()..].chars())
+                .collect(),
+            _ => line.to_string(),
+        };
+        *first = format!(" {} {}", verb, line);
+    }
+    result
+}
+
+///Last segment of the path of a type ie `String` for `std::string::String`
+fn type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(val) if val.qself.is_none() => {
+            val.path.segments.last().map(|it| it.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+///The only generic argument of a type ie `T` in `Vec<T>`
+fn generic_argument(ty: &syn::Type) -> Option<&syn::Type> {
+    match generic_arguments(ty).as_slice() {
+        [ty] => Some(ty),
+        _ => None,
+    }
+}
+
+///The type arguments of a type ie `K` and `V` in `HashMap<K, V>`
+fn generic_arguments(ty: &syn::Type) -> Vec<&syn::Type> {
+    let segment = match ty {
+        syn::Type::Path(val) => val.path.segments.last(),
+        _ => None,
+    };
+    match segment.map(|it| &it.arguments) {
+        Some(syn::PathArguments::AngleBracketed(args)) => args
+            .args
+            .iter()
+            .filter_map(|it| match it {
+                syn::GenericArgument::Type(ty) => Some(ty),
+                _ => None,
             })
-            .multiunzip();
-    let impl_block = quote::quote! {
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+///Type returned by borrowing getters ie `&str` for `String` and `&[T]` for `Vec<T>`
+fn borrowed_type(ty: &syn::Type) -> Option<syn::Type> {
+    let borrowed = match type_name(ty)?.as_str() {
+        "String" => syn::parse_quote! { &str },
+        "Vec" => {
+            let element = generic_argument(ty)?;
+            syn::parse_quote! { &[#element] }
+        }
+        _ => syn::parse_quote! { &#ty },
+    };
+    Some(borrowed)
+}
+
+///Fields which get collection accessors
+enum Collection<'a> {
+    Vec(&'a syn::Type),
+    HashMap(&'a syn::Type, &'a syn::Type),
+    Option,
+}
+
+impl Collection<'_> {
+    fn from_type(ty: &syn::Type) -> Option<Collection<'_>> {
+        let args = generic_arguments(ty);
+        match (type_name(ty)?.as_str(), args.as_slice()) {
+            ("Vec", [element]) => Some(Collection::Vec(element)),
+            ("HashMap", [key, value]) => Some(Collection::HashMap(key, value)),
+            ("Option", [_]) => Some(Collection::Option),
+            _ => None,
+        }
+    }
+}
+
+///Name of an element of a collection ie `item` for `items` and `entry` for `entries`.\
+/// Names which don't look plural like `status`, `class`, `analysis` or `news` are kept as they
+/// are. Set the name with `item = "..."` otherwise
+fn singular(name: &str) -> String {
+    //only the last word changes ie `news` in `latest_news`
+    let (prefix, word) = match name.rfind('_') {
+        Some(index) => name.split_at(index + 1),
+        None => ("", name),
+    };
+    let word = if UNCOUNTABLE_NOUNS.contains(&word) {
+        word.to_string()
+    } else if let Some((_, singular)) = IRREGULAR_PLURALS.iter().find(|(it, _)| *it == word) {
+        singular.to_string()
+    } else if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if ["sses", "xes", "ches", "shes"]
+        .iter()
+        .any(|suffix| word.ends_with(suffix))
+    {
+        word[..word.len() - 2].to_string()
+    } else if word.ends_with('s')
+        && !["ss", "us", "is"]
+            .iter()
+            .any(|suffix| word.ends_with(suffix))
+    {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    };
+    format!("{}{}", prefix, word)
+}
+
+///Accessors which don't copy the whole collection:
+/// - `Vec<T>`: `items_len`, `item_at`, `push_item`, `remove_item` and `clear_items`
+/// - `HashMap<K, V>`: `entries_len`, `get_entry`, `contains_entry`, `insert_entry`,
+///   `remove_entry` and `clear_entries`
+/// - `Option<T>`: `has_value` and `clear_value`
+///
+/// The element is named `{field}_entry` for maps whose name isn't plural ie `by_id_entry`.
+/// Only the methods which don't modify the collection are generated for `readonly` fields
+fn collection_methods(
+    field: &syn::Field,
+    options: &FieldOptions,
+    access_options: &AccessOptions,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let (f_vis, f_ident) = match &field.ident {
+        Some(ident) => (&field.vis, ident),
+        None => {
+            return match &options.collection {
+                Some(attr) => Err(syn::Error::new_spanned(
+                    attr,
+                    "collection accessors aren't supported for fields of tuple structs",
+                )),
+                None => Ok(Vec::new()),
+            }
+        }
+    };
+    let collection = match Collection::from_type(&field.ty) {
+        Some(collection) => collection,
+        None if options.collection.is_some() => {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "expected a Vec, HashMap or Option to have collection accessors",
+            ))
+        }
+        None => return Ok(Vec::new()),
+    };
+    let plural = f_ident.unraw().to_string();
+    let item = match &options.item {
+        Some(item) => item.to_string(),
+        None => singular(&plural),
+    };
+    let len = format_ident!("{}_len", plural);
+    let clear = format_ident!("clear_{}", plural);
+    let len_doc = format!(" Returns the number of elements in `{}`", plural);
+    let clear_doc = format!(" Removes all the elements of `{}`", plural);
+    let mut methods = vec![];
+    match collection {
+        Collection::Vec(element) => {
+            let at = format_ident!("{}_at", item);
+            let push = format_ident!("push_{}", item);
+            let remove = format_ident!("remove_{}", item);
+            let at_doc = format!(
+                " Returns the element of `{}` at `index`. Panics if `index` is out of bounds",
+                plural
+            );
+            let getter_kind = access_options
+                .getters
+                .unwrap_or_else(|| GetterKind::infer(element));
+            getter_kind.check(element)?;
+            let getter = match getter_kind {
+                GetterKind::Copy => quote! {
+                    #f_vis fn #at(&self, index: usize) -> #element {
+                        self.#f_ident[index]
+                    }
+                },
+                GetterKind::Borrow => {
+                    let borrowed =
+                        borrowed_type(element).unwrap_or_else(|| syn::parse_quote! { &#element });
+                    quote! {
+                        #f_vis fn #at(&self, index: usize) -> #borrowed {
+                            &self.#f_ident[index]
+                        }
+                    }
+                }
+                GetterKind::Clone => quote! {
+                    #f_vis fn #at(&self, index: usize) -> #element {
+                        self.#f_ident[index].clone()
+                    }
+                },
+            };
+            methods.push(quote! {
+                #[doc = #len_doc]
+                #[generate_interface]
+                #f_vis fn #len(&self) -> usize {
+                    self.#f_ident.len()
+                }
+                #[doc = #at_doc]
+                #[generate_interface]
+                #getter
+            });
+            if !options.readonly {
+                let push_doc = format!(" Appends `value` to the end of `{}`", plural);
+                let remove_doc = format!(
+                    " Removes and returns the element of `{}` at `index`. \
+                    Panics if `index` is out of bounds",
+                    plural
+                );
+                methods.push(quote! {
+                    #[doc = #push_doc]
+                    #[generate_interface]
+                    #f_vis fn #push(&mut self, value: #element) {
+                        self.#f_ident.push(value)
+                    }
+                    #[doc = #remove_doc]
+                    #[generate_interface]
+                    #f_vis fn #remove(&mut self, index: usize) -> #element {
+                        self.#f_ident.remove(index)
+                    }
+                    #[doc = #clear_doc]
+                    #[generate_interface]
+                    #f_vis fn #clear(&mut self) {
+                        self.#f_ident.clear()
+                    }
+                });
+            }
+        }
+        Collection::HashMap(key, value) => {
+            //`get_x` would clash with the getter of the field
+            let item = if item == plural {
+                format!("{}_entry", plural)
+            } else {
+                item
+            };
+            let get = format_ident!("get_{}", item);
+            let contains = format_ident!("contains_{}", item);
+            let insert = format_ident!("insert_{}", item);
+            let remove = format_ident!("remove_{}", item);
+            //primitive keys are passed by value and the rest by reference
+            let (key_param, key_arg) = match GetterKind::infer(key) {
+                GetterKind::Copy => (quote! { #key }, quote! { &key }),
+                GetterKind::Borrow => {
+                    let borrowed =
+                        borrowed_type(key).unwrap_or_else(|| syn::parse_quote! { &#key });
+                    (quote! { #borrowed }, quote! { key })
+                }
+                GetterKind::Clone => (quote! { &#key }, quote! { key }),
+            };
+            let get_doc = format!(" Returns the value of `key` in `{}`, if any", plural);
+            let contains_doc = format!(" Checks if `{}` contains `key`", plural);
+            methods.push(quote! {
+                #[doc = #len_doc]
+                #[generate_interface]
+                #f_vis fn #len(&self) -> usize {
+                    self.#f_ident.len()
+                }
+                #[doc = #get_doc]
+                #[generate_interface]
+                #f_vis fn #get(&self, key: #key_param) -> Option<#value> {
+                    self.#f_ident.get(#key_arg).cloned()
+                }
+                #[doc = #contains_doc]
+                #[generate_interface]
+                #f_vis fn #contains(&self, key: #key_param) -> bool {
+                    self.#f_ident.contains_key(#key_arg)
+                }
+            });
+            if !options.readonly {
+                let insert_doc = format!(
+                    " Sets the value of `key` in `{}`. Returns the previous value, if any",
+                    plural
+                );
+                let remove_doc = format!(
+                    " Removes `key` from `{}`. Returns its value, if any",
+                    plural
+                );
+                methods.push(quote! {
+                    #[doc = #insert_doc]
+                    #[generate_interface]
+                    #f_vis fn #insert(&mut self, key: #key, value: #value) -> Option<#value> {
+                        self.#f_ident.insert(key, value)
+                    }
+                    #[doc = #remove_doc]
+                    #[generate_interface]
+                    #f_vis fn #remove(&mut self, key: #key_param) -> Option<#value> {
+                        self.#f_ident.remove(#key_arg)
+                    }
+                    #[doc = #clear_doc]
+                    #[generate_interface]
+                    #f_vis fn #clear(&mut self) {
+                        self.#f_ident.clear()
+                    }
+                });
+            }
+        }
+        Collection::Option => {
+            let has = format_ident!("has_{}", plural);
+            let has_doc = format!(" Checks if `{}` is set", plural);
+            methods.push(quote! {
+                #[doc = #has_doc]
+                #[generate_interface]
+                #f_vis fn #has(&self) -> bool {
+                    self.#f_ident.is_some()
+                }
+            });
+            if !options.readonly {
+                let clear_doc = format!(" Sets `{}` to `None`", plural);
+                methods.push(quote! {
+                    #[doc = #clear_doc]
+                    #[generate_interface]
+                    #f_vis fn #clear(&mut self) {
+                        self.#f_ident = None
+                    }
+                });
+            }
+        }
+    }
+    Ok(methods)
+}
+
+///Generates a constructor, setters and getters for the fields of `item`.\
+/// Getters of `bool` fields are named `is_x` instead of `get_x` with `getters_style = "is"`.
+/// Fields of tuple structs are accessed by position ie `get_0` and `set_0`.
+/// Getters return primitives by value, `String` as `&str`, `Vec` of primitives as slices
+/// when both Java and C++ convert them to arrays
+/// and clone the rest unless set otherwise in `access_options` or the field attributes.
+/// See [`collection_methods`] for the accessors of `Vec`, `HashMap` and `Option` fields
+pub fn generate_impl_block(
+    item: &syn::ItemStruct,
+    access_options: &AccessOptions,
+) -> syn::Result<ItemImpl> {
+    let name = &item.ident;
+    let vis = &item.vis;
+
+    let mut errors = vec![];
+    let mut params = vec![];
+    let mut initializers = vec![];
+    let mut methods = vec![];
+    //the "# Arguments" section of the doc of the constructor
+    let mut params_docs = vec![];
+    for (index, field) in item.fields.iter().enumerate() {
+        let options = match FieldOptions::from_attrs(&field.attrs) {
+            Ok(options) => options,
+            Err(error) => {
+                errors.push(error);
+                continue;
+            }
+        };
+        let (f_vis, f_ty) = (&field.vis, &field.ty);
+        let f_docs = doc_lines(&field.attrs);
+        //the name of the parameter, the field in `self.field` and the name used in the accessors
+        let (f_ident, f_member, f_name) = match &field.ident {
+            Some(ident) => (ident.clone(), quote! { #ident }, ident.unraw().to_string()),
+            None => {
+                let index = syn::Index::from(index);
+                (
+                    format_ident!("field_{}", index),
+                    quote! { #index },
+                    index.index.to_string(),
+                )
+            }
+        };
+        if options.skip || options.no_constructor {
+            initializers.push(quote! { #f_member: Default::default() });
+        } else {
+            params.push(quote! { #f_ident: #f_ty });
+            initializers.push(quote! { #f_member: #f_ident });
+            params_docs.push(match f_docs.first() {
+                Some(doc) => format!(" * `{}` - {}", f_ident, doc.trim()),
+                None => format!(" * `{}`", f_ident),
+            });
+        }
+        if options.skip {
+            continue;
+        }
+        if !options.readonly {
+            let f_setter = match &options.setter {
+                Some(setter) => format_ident!("{}", setter),
+                None => format_ident!("set_{}", f_name),
+            };
+            let setter_docs = accessor_docs("Sets", &f_docs);
+            methods.push(quote! {
+                #(#[doc = #setter_docs])*
+                #[generate_interface]
+                #f_vis fn #f_setter(&mut self, #f_ident: #f_ty) {
+                    self.#f_member = #f_ident;
+                }
+            });
+        }
+        let f_getter = match &options.getter {
+            Some(getter) => format_ident!("{}", getter),
+            None if access_options.is_getters && field.ident.is_some() && is_bool(f_ty) => {
+                format_ident!("is_{}", f_name)
+            }
+            None => format_ident!("get_{}", f_name),
+        };
+        let getter_kind = options
+            .getter_kind
+            .or(access_options.getters)
+            .unwrap_or_else(|| GetterKind::infer(f_ty));
+        if let Err(error) = getter_kind.check(f_ty) {
+            errors.push(error);
+            continue;
+        }
+        let getter = match getter_kind {
+            GetterKind::Copy => quote! {
+                #f_vis fn #f_getter(&self) -> #f_ty {
+                    self.#f_member
+                }
+            },
+            GetterKind::Borrow => {
+                let borrowed = borrowed_type(f_ty).unwrap_or_else(|| syn::parse_quote! { &#f_ty });
+                quote! {
+                    #f_vis fn #f_getter(&self) -> #borrowed {
+                        &self.#f_member
+                    }
+                }
+            }
+            GetterKind::Clone => quote! {
+                #f_vis fn #f_getter(&self) -> #f_ty {
+                    (&self.#f_member).clone()
+                }
+            },
+        };
+        let getter_docs = accessor_docs("Returns", &f_docs);
+        methods.push(quote! {
+            #(#[doc = #getter_docs])*
+            #[generate_interface]
+            #getter
+        });
+        if options.collection.is_some() || access_options.collections {
+            match collection_methods(field, &options, access_options) {
+                Ok(mut collection_methods) => methods.append(&mut collection_methods),
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+    //`Foo { 0: field_0 }` and `Foo {}` are valid for tuple and unit structs
+    let mut constructor_docs = vec![format!(" Creates a new `{}`", name)];
+    if !params_docs.is_empty() {
+        constructor_docs.push(String::new());
+        constructor_docs.push(String::from(" # Arguments"));
+        constructor_docs.push(String::new());
+        constructor_docs.append(&mut params_docs);
+    }
+    let impl_block = quote! {
          impl #name {
+            #(#[doc = #constructor_docs])*
             #[generate_interface(constructor)]
             #vis fn new(
-                #(#f_ident: #f_ty),*
+                #(#params),*
             ) -> #name {
                 #name {
-                    #(#f_ident),*
+                    #(#initializers),*
                 }
             }
-            #(
-                #[generate_interface]
-                #f_vis fn #f_setter(&mut self, #f_ident: #f_ty) {
-                    self.#f_ident = #f_ident;
-                }
+            #(#methods)*
+        }
+    };
 
-                #[generate_interface]
-                #f_vis fn #f_getter(&self) -> #f_ty {
-                    (&self.#f_ident).clone()
+    combine_errors(errors)?;
+    Ok(syn::parse2(impl_block).unwrap())
+}
+
+///Generates `FooBuilder` for `Foo` with a method to set each field and `build` to get the `Foo`.
+/// The builder starts with `Foo::default()` so `Foo` should implement `Default`
+pub fn generate_builder(item: &syn::ItemStruct) -> syn::Result<(syn::ItemStruct, ItemImpl)> {
+    let name = &item.ident;
+    let vis = &item.vis;
+    let builder_name = format_ident!("{}Builder", name);
+    let builder_doc = format!(" Builder of `{}`", name);
+
+    let mut methods = vec![];
+    for (index, field) in item.fields.iter().enumerate() {
+        let options = FieldOptions::from_attrs(&field.attrs)?;
+        if options.skip || options.no_constructor {
+            continue;
+        }
+        let (f_vis, f_ty) = (&field.vis, &field.ty);
+        let (f_ident, f_member) = match &field.ident {
+            Some(ident) => (ident.clone(), quote! { #ident }),
+            None => {
+                let index = syn::Index::from(index);
+                (format_ident!("field_{}", index), quote! { #index })
+            }
+        };
+        let f_method = match options.setter {
+            Some(setter) => format_ident!("{}", setter),
+            None => f_ident.clone(),
+        };
+        let f_docs = accessor_docs("Sets", &doc_lines(&field.attrs));
+        methods.push(quote! {
+            #(#[doc = #f_docs])*
+            #[generate_interface]
+            #f_vis fn #f_method(mut self, #f_ident: #f_ty) -> #builder_name {
+                self.inner.#f_member = #f_ident;
+                self
+            }
+        });
+    }
+    let new_doc = format!(
+        " Creates a new `{}` with the default values of `{}`",
+        builder_name, name
+    );
+    let build_doc = format!(" Returns the `{}` with the values set", name);
+    let builder = quote! {
+        #[doc = #builder_doc]
+        #[generate_interface_doc]
+        #vis struct #builder_name {
+            inner: #name,
+        }
+    };
+    let impl_block = quote! {
+        impl #builder_name {
+            #[doc = #new_doc]
+            #[generate_interface(constructor)]
+            #vis fn new() -> #builder_name {
+                #builder_name {
+                    inner: Default::default(),
                 }
-            )*
+            }
+            #(#methods)*
+            #[doc = #build_doc]
+            #[generate_interface]
+            #vis fn build(self) -> #name {
+                self.inner
+            }
         }
     };
+    Ok((
+        syn::parse2(builder).unwrap(),
+        syn::parse2(impl_block).unwrap(),
+    ))
+}
+
+///Arguments passed to an attribute ie `constructor` in `#[generate_interface(constructor)]`.
+/// Returns an empty list if the attribute has no arguments
+pub fn attribute_args(attr: &syn::Attribute) -> Vec<NestedMeta> {
+    match attr.parse_meta() {
+        Ok(Meta::List(list)) => list.nested.into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+///Arguments of the first attribute whose name is `name`, if any
+pub fn find_attribute_args(attrs: &[syn::Attribute], name: &str) -> Option<Vec<NestedMeta>> {
+    attrs
+        .iter()
+        .find(|attr| {
+            attr.path
+                .segments
+                .last()
+                .map(|it| it.ident == name)
+                .unwrap_or_default()
+        })
+        .map(attribute_args)
+}
+
+///Checks if a flag such as `constructor` is among the arguments
+pub fn has_flag(args: &[NestedMeta], name: &str) -> bool {
+    args.iter()
+        .any(|it| matches!(it, NestedMeta::Meta(Meta::Path(path)) if path.is_ident(name)))
+}
+
+///Values of all the `name = "value"` arguments, for arguments which can be repeated
+pub fn string_args(args: &[NestedMeta], name: &str) -> Vec<String> {
+    args.iter()
+        .filter_map(|it| string_arg(std::slice::from_ref(it), name))
+        .collect()
+}
+
+///Value of a `name = "value"` argument
+pub fn string_arg(args: &[NestedMeta], name: &str) -> Option<String> {
+    args.iter().find_map(|it| match it {
+        NestedMeta::Meta(Meta::NameValue(MetaNameValue {
+            path,
+            lit: Lit::Str(val),
+            ..
+        })) if path.is_ident(name) => Some(val.value()),
+        _ => None,
+    })
+}
+
+///Combines the errors so they're all reported instead of only the first one
+pub fn combine_errors(errors: Vec<syn::Error>) -> syn::Result<()> {
+    match errors.into_iter().reduce(|mut first, other| {
+        first.combine(other);
+        first
+    }) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_builder, generate_impl_block, strip_field_attributes, AccessOptions};
+    use quote::ToTokens;
+
+    fn method_names(item: &syn::ItemImpl) -> Vec<String> {
+        item.items
+            .iter()
+            .filter_map(|it| match it {
+                syn::ImplItem::Method(method) => Some(method.sig.ident.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn field_attributes() {
+        let mut item: syn::ItemStruct = syn::parse_str(
+            "struct Foo {
+                #[rifgen(skip)]
+                cache: Vec<u8>,
+                #[rifgen(readonly)]
+                id: i64,
+                #[rifgen(getter = \"timeout_ms\", setter = \"update_timeout\")]
+                timeout: u32,
+                #[doc = \"enabled\"]
+                enabled: bool,
+                #[rifgen(no_constructor)]
+                name: String,
+            }",
+        )
+        .unwrap();
+        let impl_block = generate_impl_block(&item, &AccessOptions::default()).unwrap();
+        assert_eq!(
+            method_names(&impl_block),
+            vec![
+                "new",
+                "get_id",
+                "update_timeout",
+                "timeout_ms",
+                "set_enabled",
+                "get_enabled",
+                "set_name",
+                "get_name"
+            ]
+        );
+        match &impl_block.items[0] {
+            syn::ImplItem::Method(new) => assert_eq!(new.sig.inputs.len(), 3),
+            _ => panic!("the constructor should be the first item"),
+        }
+        let options = AccessOptions::from_args(&[syn::parse_quote!(getters_style = "is")]).unwrap();
+        let impl_block = generate_impl_block(&item, &options).unwrap();
+        assert!(method_names(&impl_block).contains(&String::from("is_enabled")));
+        assert!(AccessOptions::from_args(&[syn::parse_quote!(getters_style = "has")]).is_err());
+        strip_field_attributes(&mut item);
+        let item = item.to_token_stream().to_string();
+        assert!(!item.contains("rifgen") && item.contains("doc"));
+    }
+
+    #[test]
+    fn tuple_and_unit_structs() {
+        let item: syn::ItemStruct =
+            syn::parse_str("struct Meters(f64, #[rifgen(getter = \"unit\")] String);").unwrap();
+        assert_eq!(
+            method_names(&generate_impl_block(&item, &AccessOptions::default()).unwrap()),
+            vec!["new", "set_0", "get_0", "set_1", "unit"]
+        );
+        let item: syn::ItemStruct = syn::parse_str("struct Marker;").unwrap();
+        assert_eq!(
+            method_names(&generate_impl_block(&item, &AccessOptions::default()).unwrap()),
+            vec!["new"]
+        );
+    }
+
+    #[test]
+    fn docs() {
+        let item: syn::ItemStruct = syn::parse_str(
+            "struct Foo {
+                /// Timeout in milliseconds
+                /// Defaults to 30
+                timeout: u32,
+                /// URL of the server
+                url: String,
+                id: i64,
+            }",
+        )
+        .unwrap();
+        let docs = generate_impl_block(&item, &AccessOptions::default())
+            .unwrap()
+            .items
+            .iter()
+            .map(|it| match it {
+                syn::ImplItem::Method(method) => crate::doc_lines(&method.attrs),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            docs[0],
+            vec![
+                " Creates a new `Foo`",
+                "",
+                " # Arguments",
+                "",
+                " * `timeout` - Timeout in milliseconds",
+                " * `url` - URL of the server",
+                " * `id`"
+            ]
+        );
+        assert_eq!(
+            docs[1],
+            vec![" Sets timeout in milliseconds", " Defaults to 30"]
+        );
+        assert_eq!(
+            docs[2],
+            vec![" Returns timeout in milliseconds", " Defaults to 30"]
+        );
+        assert_eq!(docs[4], vec![" Returns URL of the server"]);
+        assert!(docs[5].is_empty());
+    }
+
+    #[test]
+    fn builder() {
+        let item: syn::ItemStruct = syn::parse_str(
+            "pub struct Foo {
+                timeout: u32,
+                #[rifgen(skip)]
+                cache: Vec<u8>,
+                #[rifgen(setter = \"with_name\")]
+                name: String,
+            }",
+        )
+        .unwrap();
+        let (builder, impl_block) = generate_builder(&item).unwrap();
+        assert_eq!(builder.ident, "FooBuilder");
+        assert_eq!(
+            method_names(&impl_block),
+            vec!["new", "timeout", "with_name", "build"]
+        );
+    }
+
+    #[test]
+    fn collections() {
+        let item: syn::ItemStruct = syn::parse_str(
+            "struct Foo {
+                #[rifgen(collection)]
+                items: Vec<Item>,
+                #[rifgen(collection, readonly)]
+                entries: HashMap<String, Item>,
+                #[rifgen(collection, item = \"child\")]
+                children: Option<Item>,
+                names: Vec<String>,
+            }",
+        )
+        .unwrap();
+        assert_eq!(
+            method_names(&generate_impl_block(&item, &AccessOptions::default()).unwrap()),
+            vec![
+                "new",
+                "set_items",
+                "get_items",
+                "items_len",
+                "item_at",
+                "push_item",
+                "remove_item",
+                "clear_items",
+                "get_entries",
+                "entries_len",
+                "get_entry",
+                "contains_entry",
+                "set_children",
+                "get_children",
+                "has_children",
+                "clear_children",
+                "set_names",
+                "get_names",
+            ]
+        );
+        let args: syn::AttributeArgs = vec![syn::parse_quote!(collections)];
+        let impl_block =
+            generate_impl_block(&item, &AccessOptions::from_args(&args).unwrap()).unwrap();
+        let names = method_names(&impl_block);
+        assert!(names.contains(&String::from("name_at")));
+        assert!(names.contains(&String::from("clear_names")));
+    }
+
+    #[test]
+    fn singular() {
+        let cases = [
+            ("items", "item"),
+            ("entries", "entry"),
+            ("boxes", "box"),
+            ("classes", "class"),
+            ("status", "status"),
+            ("bus", "bus"),
+            ("analysis", "analysis"),
+            ("latest_news", "latest_news"),
+            ("data", "data"),
+            ("series", "series"),
+            ("tv_series", "tv_series"),
+            ("species", "species"),
+            ("movies", "movie"),
+            ("favorite_movies", "favorite_movie"),
+            ("buses", "bus"),
+            ("school_buses", "school_bus"),
+            ("statuses", "status"),
+            ("ss_items", "ss_item"),
+            ("news_entries", "news_entry"),
+            ("address_boxes", "address_box"),
+        ];
+        for (plural, singular) in cases {
+            assert_eq!(crate::singular(plural), singular);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let item: syn::ItemStruct = syn::parse_str(
+            "struct Foo {
+                #[rifgen(getter = \"not valid\", unknown)]
+                a: i32,
+                #[rifgen(collection)]
+                b: String,
+            }",
+        )
+        .unwrap();
+        let error = generate_impl_block(&item, &AccessOptions::default()).unwrap_err();
+        assert_eq!(error.into_iter().count(), 3);
+        let args: syn::AttributeArgs = vec![
+            syn::parse_quote!(getters = "none"),
+            syn::parse_quote!(other),
+        ];
+        let error = AccessOptions::from_args(&args).err().unwrap();
+        assert_eq!(error.into_iter().count(), 2);
+    }
 
-    syn::parse2(impl_block).unwrap()
+    #[test]
+    fn getter_kinds() {
+        let item: syn::ItemStruct = syn::parse_str(
+            "struct Foo {
+                a: i32,
+                b: String,
+                c: Vec<u8>,
+                g: Vec<i32>,
+                d: Vec<Foo>,
+                e: Bar,
+                #[rifgen(getter_kind = \"borrow\")]
+                f: Bar,
+            }",
+        )
+        .unwrap();
+        let return_types = |options: &AccessOptions| {
+            generate_impl_block(&item, options)
+                .unwrap()
+                .items
+                .iter()
+                .filter_map(|it| match it {
+                    syn::ImplItem::Method(method)
+                        if method.sig.ident.to_string().starts_with("get") =>
+                    {
+                        Some(method.sig.output.to_token_stream().to_string())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            return_types(&AccessOptions::default()),
+            vec![
+                "-> i32",
+                "-> & str",
+                "-> Vec < u8 >",
+                "-> & [i32]",
+                "-> Vec < Foo >",
+                "-> Bar",
+                "-> & Bar"
+            ]
+        );
+        let args: syn::AttributeArgs = vec![syn::parse_quote!(getters = "clone")];
+        assert_eq!(
+            return_types(&AccessOptions::from_args(&args).unwrap()),
+            vec![
+                "-> i32",
+                "-> String",
+                "-> Vec < u8 >",
+                "-> Vec < i32 >",
+                "-> Vec < Foo >",
+                "-> Bar",
+                "-> & Bar"
+            ]
+        );
+        //the types which are never Copy are reported
+        let copy = AccessOptions::from_args(&[syn::parse_quote!(getters = "copy")]).unwrap();
+        let generate = |item: &str, options: &AccessOptions| {
+            generate_impl_block(&syn::parse_str(item).unwrap(), options).is_ok()
+        };
+        assert!(generate("struct Foo { a: Point, b: u8 }", &copy));
+        assert!(!generate("struct Foo { a: Point, b: Vec<i32> }", &copy));
+        assert!(!generate(
+            "struct Foo { #[rifgen(getter_kind = \"copy\")] b: String }",
+            &AccessOptions::default()
+        ));
+    }
 }
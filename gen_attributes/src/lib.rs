@@ -1,79 +1,674 @@
 extern crate proc_macro;
 
 use proc_macro::TokenStream;
-use rifgen_attributes_utils::generate_impl_block;
+use rifgen_attributes_utils::callbacks::{
+    callback_shape_errors, callback_supertraits, requires_sized_self, skip_reason, SkipReason,
+};
+use rifgen_attributes_utils::enum_values::enum_values;
+use rifgen_attributes_utils::ffi_types::{FfiLanguage, TypeCheck};
+use rifgen_attributes_utils::{
+    combine_errors, find_attribute_args, generate_builder, generate_impl_block, has_flag,
+    string_arg, strip_field_attributes, AccessOptions, SHARED_OWNERSHIP,
+};
+use syn::spanned::Spanned;
 
+/// Exports a method, function, const, enum or callback trait.
+///
+/// On an impl block, all the `pub` methods are exported. Exclude a method with
+/// `#[generate_interface(skip)]` and mark constructors with `#[generate_interface(constructor)]`
+///
+/// On a trait, all the methods are exported in the callback except those marked with
+/// `#[generate_interface(skip)]`, generic methods and methods requiring `Self: Sized`.
+/// These should have a default implementation since the foreign implementation can't
+/// provide them and generic methods should require `Self: Sized`.
+/// Only `Send` and `Sync` are allowed as supertraits and the exported methods should take
+/// `&self` or `&mut self`. Generic callbacks, associated types and consts and async methods
+/// aren't supported
+///
+/// With the `check-java` or `check-cpp` feature, the argument and return types are checked
+/// against the types flapigen supports. `#[generate_interface(unchecked)]` skips the check
+///
+/// `#[generate_interface(annotate = "@Keep")]` adds a Java annotation to the method.
+/// It can be repeated. See `rifgen::ForeignAnnotation`
+///
+/// `#[generate_interface(exception = "com.example.FooException")]` on an enum or struct maps
+/// it to the Java exception thrown by methods returning it as the error of a `Result`.
+/// It's then not exported as an enum
+///
+/// Async methods are exported as methods taking a completion callback. They can't borrow
+/// `self` or their arguments mutably. See `rifgen::Generator::async_executor`
+///
+/// Consts, including associated consts, are exported as static getters ie `getMaxSize`
+///
+/// Enums with discriminants or an integer `#[repr]` get a `{Enum}Values` class converting
+/// the variants to their values and back. With the check features, the values should fit
+/// the integer type in the foreign language
+///
+/// Enums with fields can't be exported as flapigen enums. `#[generate_interface(tagged)]` exports
+/// them as a class instead, with a static factory for each variant, `kind` returning the variant
+/// as the `{Enum}Kind` enum and getters like `getRectW` for the fields, which fail on the other
+/// variants. The fields should implement `Clone`
+///
+/// `#[generate_interface(flags)]` on an enum whose variants are flags ie `Read = 1` exports the
+/// `{Enum}Flags` class instead, holding a set of flags with `contains`, `insert`, `remove`,
+/// `bits`, `fromBits` and a static getter for each flag
+///
+/// Closure arguments are passed by foreign code as callbacks generated for each closure
+/// signature. They can't be `Sync`
 #[proc_macro_attribute]
 pub fn generate_interface(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let item = syn::parse(item).unwrap();
-    let mut is_func = false;
-
-    match item {
-        syn::Item::Fn(ref fun) => {
-            //general function
-            is_func = true;
+    let attr = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let mut item = syn::parse_macro_input!(item as syn::Item);
+    let mut errors = vec![];
+    let mut unchecked = false;
+    let mut constructor = None;
+    let mut annotations = vec![];
+    let mut exception = None;
+    let mut tagged = None;
+    let mut flags = None;
+    for arg in &attr {
+        let args = std::slice::from_ref(arg);
+        if has_flag(args, "constructor") {
+            constructor = Some(arg)
+        } else if has_flag(args, "unchecked") || has_flag(args, "skip") {
+            unchecked = true
+        } else if let Some(annotation) = string_arg(args, "annotate") {
+            check_annotation(arg, &annotation, &mut errors);
+            annotations.push(arg)
+        } else if let Some(class) = string_arg(args, "exception") {
+            if !is_java_class(&class) {
+                errors.push(syn::Error::new_spanned(
+                    arg,
+                    "exception should be the full name of a Java class ie \
+                    \"com.example.FooException\"",
+                ))
+            }
+            exception = Some(arg)
+        } else if has_flag(args, "tagged") {
+            tagged = Some(arg)
+        } else if has_flag(args, "flags") {
+            flags = Some(arg)
+        } else {
+            errors.push(syn::Error::new_spanned(
+                arg,
+                "only constructor, skip, unchecked, annotate, exception, tagged and flags \
+                attributes are supported for now",
+            ))
+        }
+    }
+    let check = !unchecked && !checked_languages().is_empty();
+    //added after the item
+    let mut extra = None;
+    let is_func = match &mut item {
+        syn::Item::Fn(fun) => {
             //generics not supported
-            let gene = &fun.sig.generics;
-            assert!(gene.gt_token.is_none(), "Generics not yet supported");
-            assert!(gene.lt_token.is_none(), "Generics not yet supported")
-        }
-        syn::Item::Enum(_) => {}
-        syn::Item::Trait(_) => {}
-        syn::Item::Struct(_) => panic!(
-            "Annotate methods of this struct instead. \
-        To use enable doc comments on this struct use #[generate_interface_doc] macro instead."
-        ),
-        _ => panic!("unsuppoted type"),
-    }
-    let attr = attr.to_string();
-    if !attr.is_empty() {
-        assert_eq!(
-            attr, "constructor",
-            "only constructor attributes are supported for now"
-        );
+            let generics = &fun.sig.generics;
+            if generics.lt_token.is_some() || generics.where_clause.is_some() {
+                errors.push(syn::Error::new_spanned(
+                    generics,
+                    "Generics not yet supported",
+                ))
+            }
+            check_async(&fun.sig, constructor, &mut errors);
+            if check {
+                check_body(&fun.sig, &mut fun.block, constructor.is_some(), &mut errors)
+            }
+            true
+        }
+        syn::Item::Impl(i) => {
+            if let Some((_, path, _)) = &i.trait_ {
+                errors.push(syn::Error::new_spanned(
+                    path,
+                    "Use this macro on impl blocks without traits only",
+                ))
+            }
+            if !i.generics.params.is_empty() {
+                errors.push(syn::Error::new_spanned(
+                    &i.generics,
+                    "Generics not yet supported",
+                ))
+            }
+            //methods with their own #[generate_interface] are checked when it's expanded
+            let methods = i.items.iter_mut().filter_map(|it| match it {
+                syn::ImplItem::Method(method)
+                    if matches!(method.vis, syn::Visibility::Public(_))
+                        && find_attribute_args(&method.attrs, "generate_interface").is_none() =>
+                {
+                    Some(method)
+                }
+                _ => None,
+            });
+            for method in methods {
+                let generics = &method.sig.generics;
+                if generics.lt_token.is_some() || generics.where_clause.is_some() {
+                    errors.push(syn::Error::new_spanned(
+                        generics,
+                        "Generics not yet supported. Exclude the method with \
+                        #[generate_interface(skip)]",
+                    ))
+                }
+                check_async(&method.sig, None, &mut errors);
+                if check {
+                    check_body(&method.sig, &mut method.block, false, &mut errors)
+                }
+            }
+            false
+        }
+        //error types mapped to exceptions aren't exported
+        syn::Item::Enum(e) if exception.is_some() => {
+            extra = exception_type_impl(&e.ident, &e.generics);
+            false
+        }
+        syn::Item::Struct(s) if exception.is_some() => {
+            extra = exception_type_impl(&s.ident, &s.generics);
+            false
+        }
+        //exported as a class
+        syn::Item::Enum(e) if tagged.is_some() => {
+            let mut assertions = vec![];
+            if check {
+                let types = e
+                    .variants
+                    .iter()
+                    .flat_map(|it| it.fields.iter())
+                    .map(|it| &it.ty);
+                let signature = syn::parse_quote! { fn new(#(_: #types),*) };
+                assertions = check_types(&signature, &mut errors);
+            }
+            if let Some(impl_block) = exported_type_impl(&e.ident, &e.generics) {
+                extra = Some(quote::quote! {
+                    #impl_block
+                    const _: fn() = || {
+                        #(#assertions)*
+                    };
+                });
+            }
+            false
+        }
+        syn::Item::Enum(e) => {
+            //flapigen enums can't hold data
+            let data_variants = e
+                .variants
+                .iter()
+                .filter(|it| !matches!(it.fields, syn::Fields::Unit))
+                .collect::<Vec<_>>();
+            for variant in &data_variants {
+                errors.push(syn::Error::new_spanned(
+                    &variant.fields,
+                    format!(
+                        "{}::{} has fields, which flapigen enums can't hold. Export {} as a \
+                        class with #[generate_interface(tagged)]",
+                        e.ident, variant.ident, e.ident
+                    ),
+                ))
+            }
+            if check && data_variants.is_empty() {
+                for language in checked_languages() {
+                    if let Some(Err(error)) = enum_values(e, language) {
+                        errors.push(error)
+                    }
+                }
+            }
+            //only the set of flags is exported
+            if flags.is_some() {
+                if let Some(variant) = e.variants.iter().find(|it| it.discriminant.is_none()) {
+                    errors.push(syn::Error::new_spanned(
+                        variant,
+                        "set the bits of each flag ie `Read = 1`",
+                    ))
+                }
+            } else {
+                extra = exported_type_impl(&e.ident, &e.generics);
+            }
+            false
+        }
+        syn::Item::Trait(t) => {
+            errors.append(&mut callback_supertraits(t).1);
+            errors.append(&mut callback_shape_errors(t));
+            let mut assertions = vec![];
+            for it in &mut t.items {
+                let method = match it {
+                    syn::TraitItem::Method(method) => method,
+                    _ => continue,
+                };
+                if let Some(args) = find_attribute_args(&method.attrs, "generate_interface") {
+                    errors.extend(
+                        args.iter()
+                            .filter(|arg| !has_flag(std::slice::from_ref(*arg), "skip"))
+                            .map(|arg| {
+                                syn::Error::new_spanned(
+                                    arg,
+                                    "only the skip attribute is supported on callback methods",
+                                )
+                            }),
+                    )
+                }
+                let reason = skip_reason(method);
+                //the attribute can't be expanded on a method without a body
+                method
+                    .attrs
+                    .retain(|attr| !attr.path.is_ident("generate_interface"));
+                if reason == Some(SkipReason::Generic) && !requires_sized_self(method) {
+                    errors.push(syn::Error::new_spanned(
+                        &method.sig.generics,
+                        "add `where Self: Sized` to generic methods so the callback can be \
+                        used as a trait object",
+                    ))
+                }
+                match reason {
+                    Some(reason) if method.default.is_none() => {
+                        errors.push(syn::Error::new_spanned(
+                            &method.sig,
+                            format!(
+                                "this method is skipped since {} so it needs a default \
+                                implementation. The foreign implementation can't provide it",
+                                reason.description()
+                            ),
+                        ))
+                    }
+                    Some(_) => {}
+                    None if check => assertions.append(&mut check_types(&method.sig, &mut errors)),
+                    None => {}
+                }
+            }
+            //generic callbacks aren't supported by flapigen
+            if !checked_languages().is_empty() && t.generics.params.is_empty() {
+                let name = &t.ident;
+                extra = Some(quote::quote! {
+                    impl ::rifgen::ExportedType for dyn #name {}
+                    const _: fn() = || {
+                        #(#assertions)*
+                    };
+                });
+            }
+            false
+        }
+        //associated consts are parsed as items too
+        syn::Item::Const(c) => {
+            //exported types can't be asserted in impl blocks so only the built in types are checked
+            if check {
+                let ty = &c.ty;
+                let signature = syn::parse_quote! { fn get() -> #ty };
+                for language in checked_languages() {
+                    errors.append(&mut TypeCheck::signature(&signature, language).errors)
+                }
+            }
+            false
+        }
+        syn::Item::Struct(s) => {
+            errors.push(syn::Error::new_spanned(
+                &s.ident,
+                "Annotate methods of this struct instead. \
+            To use enable doc comments on this struct use #[generate_interface_doc] macro instead.",
+            ));
+            false
+        }
+        _ => {
+            errors.push(syn::Error::new_spanned(
+                item_head(&item),
+                "unsupported item. Use this macro on functions, methods, consts, enums and traits",
+            ));
+            false
+        }
+    };
+    if let Some(arg) =
+        exception.filter(|_| !matches!(item, syn::Item::Enum(_) | syn::Item::Struct(_)))
+    {
+        errors.push(syn::Error::new_spanned(
+            arg,
+            "use exception on the error enums and structs returned in Results",
+        ))
+    }
+    if let Some(arg) = tagged.filter(|_| !matches!(item, syn::Item::Enum(_))) {
+        errors.push(syn::Error::new_spanned(
+            arg,
+            "use tagged on enums with fields",
+        ))
+    }
+    if let Some(arg) = flags {
+        if !matches!(item, syn::Item::Enum(_)) {
+            errors.push(syn::Error::new_spanned(arg, "use flags on enums"))
+        } else if tagged.is_some() {
+            errors.push(syn::Error::new_spanned(arg, "tagged enums can't be flags"))
+        }
+    }
+    if let Some(arg) = constructor.filter(|_| !is_func) {
+        errors.push(syn::Error::new_spanned(arg, "call constructor on function"))
+    }
+    //flapigen only passes the attributes of methods to the callbacks adding the annotations
+    for arg in annotations {
         if !is_func {
-            panic!("call constructor on function")
+            errors.push(syn::Error::new_spanned(
+                arg,
+                "annotate methods individually. Use #[generate_interface_doc(annotate = \"...\")] \
+                for structs",
+            ))
+        } else if constructor.is_some() {
+            errors.push(syn::Error::new_spanned(
+                arg,
+                "annotations can't be added to constructors",
+            ))
         }
     }
-    let y = quote::quote! {
-        #item
-    };
-    y.into()
+    expand(quote::quote! { #item #extra }, combine_errors(errors))
 }
 
+/// Preserves the doc comment of a struct.
+///
+/// `#[generate_interface_doc(object_methods)]` also exports `equals`, `hashCode` and `toString`
+/// for the `PartialEq`, `Hash` and `Display` (or `Debug`) implementations of the struct.
+///
+/// `#[generate_interface_doc(shared = "arc_mutex")]` makes the constructors return `Arc<Mutex<T>>`
+/// (or `Rc<RefCell<T>>` with `"rc_refcell"`) so the instances can be shared ie between threads
+///
+/// `#[generate_interface_doc(annotate = "@Keep")]` adds a Java annotation to the class
+///
+/// With the `check-java` or `check-cpp` feature, it also implements `rifgen::ExportedType`
+/// so the struct can be used by methods whose types are checked
 #[proc_macro_attribute]
 pub fn generate_interface_doc(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let item = syn::parse(item).unwrap();
-    match item {
-        syn::Item::Struct(_) => {}
-        _ => panic!("Use this macro on only struct`"),
-    }
-    assert!(attr.is_empty(), "No attributes allowed yet");
-    let fin = quote::quote! {
-        #item
+    let attr = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let item = syn::parse_macro_input!(item as syn::Item);
+    let mut errors = vec![];
+    let exported_type = match &item {
+        syn::Item::Struct(s) => exported_type_impl(&s.ident, &s.generics),
+        _ => {
+            errors.push(syn::Error::new_spanned(
+                item_head(&item),
+                "Use this macro on only struct",
+            ));
+            None
+        }
     };
-    fin.into()
+    for arg in &attr {
+        let args = std::slice::from_ref(arg);
+        if has_flag(args, "object_methods") {
+            continue;
+        }
+        if let Some(annotation) = string_arg(args, "annotate") {
+            check_annotation(arg, &annotation, &mut errors);
+            continue;
+        }
+        match string_arg(args, "shared") {
+            Some(val) if SHARED_OWNERSHIP.contains(&val.as_str()) => {}
+            Some(_) => errors.push(syn::Error::new_spanned(
+                arg,
+                format!("shared should be one of {:?}", SHARED_OWNERSHIP),
+            )),
+            None => errors.push(syn::Error::new_spanned(
+                arg,
+                "only object_methods, shared and annotate attributes are supported for now",
+            )),
+        }
+    }
+    expand(
+        quote::quote! { #item #exported_type },
+        combine_errors(errors),
+    )
 }
 
 /// Automatically generate constructor, setters and getters from a struct definition.
-/// The getters should implement `Clone`.
 ///
-/// C++ interfaces wouldn't need to implement `Clone`, however, I'm yet to implement that.
+/// Getters return primitives by value, `String` as `&str` and `Vec` of primitives as slices
+/// when both Java and C++ convert them to arrays ie `Vec<i32>` but not `Vec<u8>`.
+/// Other fields are cloned so they should implement `Clone`.
+/// `#[generate_access_methods(getters = "...")]` sets how all the getters return their field
+/// with one of `auto` (the default), `copy`, `borrow` or `clone`. C++ interfaces,
+/// for instance, may use `borrow` so the fields needn't implement `Clone`
+///
+/// Fields can be annotated with `#[rifgen(...)]`:
+/// - `skip`: no accessors and the field is set with `Default::default()` in the constructor
+/// - `readonly`: no setter
+/// - `no_constructor`: the field is set with `Default::default()` in the constructor
+/// - `getter = "name"` and `setter = "name"`: rename the accessors
+/// - `getter_kind = "..."`: one of `copy`, `borrow` or `clone` for the getter of this field
+/// - `collection`: also generate accessors which don't copy the whole collection for
+///   `Vec`, `HashMap` and `Option` fields ie `items_len`, `item_at`, `push_item`, `remove_item`
+///   and `clear_items` for `items: Vec<Item>`
+/// - `item = "name"`: name of an element of the collection in these accessors. It defaults to
+///   the singular of the field name, ie `entry` for `entries`, or the field name itself when it
+///   doesn't look plural like `status`
+///
+/// `#[generate_access_methods(getters_style = "is")]` names the getters of `bool` fields `is_x`
+/// instead of `get_x`.
+///
+/// Tuple structs get positional accessors ie `get_0` and `set_0` and unit structs only get a constructor
+///
+/// Doc comments of the fields are carried over to their getters and setters
+/// and listed in the "# Arguments" section of the constructor doc
+///
+/// `#[generate_access_methods(collections)]` generates the collection accessors for all the
+/// `Vec`, `HashMap` and `Option` fields.
+///
+/// `#[generate_access_methods(builder)]` also generates a `FooBuilder` with a chained setter
+/// for each field and a `build` method. The builder starts from `Foo::default()`
+/// so the struct should implement `Default`
 #[proc_macro_attribute]
-pub fn generate_access_methods(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let ast: syn::Item = syn::parse(item).unwrap();
+pub fn generate_access_methods(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = syn::parse_macro_input!(attr as syn::AttributeArgs);
+    let mut s = match syn::parse_macro_input!(item as syn::Item) {
+        syn::Item::Struct(s) => s,
+        item => {
+            let error = syn::Error::new_spanned(item_head(&item), "Use this macro on only struct");
+            return expand(quote::quote! { #item }, Err(error));
+        }
+    };
+    let mut errors = vec![];
+    //the accessors are still generated with the default options so only the invalid
+    //options are reported
+    let options = AccessOptions::from_args(&attr).unwrap_or_else(|error| {
+        errors.push(error);
+        AccessOptions::default()
+    });
+    let impl_block = generate_impl_block(&s, &options)
+        .map_err(|error| errors.push(error))
+        .ok();
+    //the builder has the same field attributes so their errors were already reported
+    let builder = if options.builder && impl_block.is_some() {
+        generate_builder(&s).ok()
+    } else {
+        None
+    };
+    let (builder, builder_impl) = builder.unzip();
+    strip_field_attributes(&mut s);
+    let fin = quote::quote! {
+        #[generate_interface_doc]
+        #s
 
-    match ast {
-        syn::Item::Struct(s) => {
-            let impl_block = generate_impl_block(&s);
-            let fin = quote::quote! {
-                #[generate_interface_doc]
-                #s
+        #impl_block
 
-                #impl_block
-            };
-            fin.into()
+        #builder
+
+        #builder_impl
+    };
+    expand(fin, combine_errors(errors))
+}
+
+///Languages the types of the exported methods are checked against.
+/// Set with the `check-java` and `check-cpp` features
+fn checked_languages() -> Vec<FfiLanguage> {
+    let mut languages = vec![];
+    if cfg!(feature = "check-java") {
+        languages.push(FfiLanguage::Java)
+    }
+    if cfg!(feature = "check-cpp") {
+        languages.push(FfiLanguage::Cpp)
+    }
+    languages
+}
+
+///Checks the types of `signature` for each language. Returns statements asserting that the
+/// types which aren't built in are exported since the macro can't tell
+fn check_types(
+    signature: &syn::Signature,
+    errors: &mut Vec<syn::Error>,
+) -> Vec<proc_macro2::TokenStream> {
+    let mut exported: Vec<syn::Type> = vec![];
+    let mut exceptions: Vec<syn::Type> = vec![];
+    for language in checked_languages() {
+        let mut check = TypeCheck::signature(signature, language);
+        errors.append(&mut check.errors);
+        for ty in check.exported {
+            if !exported.contains(&ty) {
+                exported.push(ty)
+            }
+        }
+        for ty in check.exceptions {
+            if !exceptions.contains(&ty) {
+                exceptions.push(ty)
+            }
         }
-        _ => panic!("Use this macro on only struct`"),
     }
+    exported
+        .into_iter()
+        .map(|ty| quote::quote_spanned! { ty.span() => ::rifgen::assert_exported::<#ty>(); })
+        .chain(
+            exceptions.into_iter().map(
+                |ty| quote::quote_spanned! { ty.span() => ::rifgen::assert_exception::<#ty>(); },
+            ),
+        )
+        .collect()
+}
+
+///Checks the types of a function and adds the assertions for the exported types to its body
+fn check_body(
+    signature: &syn::Signature,
+    block: &mut syn::Block,
+    constructor: bool,
+    errors: &mut Vec<syn::Error>,
+) {
+    let mut signature = signature.clone();
+    if constructor {
+        //constructors return the class itself
+        signature.output = syn::ReturnType::Default;
+    }
+    let assertions = check_types(&signature, errors);
+    block.stmts.splice(
+        0..0,
+        assertions.into_iter().map(|it| syn::parse_quote! { #it }),
+    );
+}
+
+///Async methods are called by wrappers spawning the future so they can't borrow their
+/// arguments mutably
+fn check_async(
+    signature: &syn::Signature,
+    constructor: Option<&syn::NestedMeta>,
+    errors: &mut Vec<syn::Error>,
+) {
+    if signature.asyncness.is_none() {
+        return;
+    }
+    if let Some(arg) = constructor {
+        errors.push(syn::Error::new_spanned(arg, "constructors can't be async"))
+    }
+    for input in &signature.inputs {
+        let is_mutable = match input {
+            syn::FnArg::Receiver(receiver) => receiver.mutability.is_some(),
+            syn::FnArg::Typed(arg) => {
+                matches!(&*arg.ty, syn::Type::Reference(val) if val.mutability.is_some())
+            }
+        };
+        if is_mutable {
+            errors.push(syn::Error::new_spanned(
+                input,
+                "async methods can't borrow mutably since the future outlives the call",
+            ))
+        }
+    }
+}
+
+///Implements `rifgen::ExceptionType` for the error type when the types are checked
+fn exception_type_impl(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+) -> Option<proc_macro2::TokenStream> {
+    if checked_languages().is_empty() {
+        return None;
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Some(quote::quote! {
+        impl #impl_generics ::rifgen::ExceptionType for #name #ty_generics #where_clause {}
+    })
+}
+
+///`com.example.FooException` or `com/example/FooException`
+fn is_java_class(class: &str) -> bool {
+    class.split(['.', '/']).all(|it| {
+        it.starts_with(|it: char| it.is_alphabetic() || it == '_')
+            && it
+                .chars()
+                .all(|it| it.is_alphanumeric() || it == '_' || it == '$')
+    })
+}
+
+///Annotations are added to the generated Java code so they should look like `@Keep`
+fn check_annotation(arg: &syn::NestedMeta, annotation: &str, errors: &mut Vec<syn::Error>) {
+    let name = annotation.strip_prefix('@').unwrap_or_default();
+    if !name.starts_with(|it: char| it.is_alphabetic()) {
+        errors.push(syn::Error::new_spanned(
+            arg,
+            "annotations should start with @ followed by the name of the annotation ie \"@Keep\"",
+        ))
+    }
+}
+
+///The keyword and name of the item ie `enum Foo` so the errors don't cover its whole body
+fn item_head(item: &syn::Item) -> proc_macro2::TokenStream {
+    match item {
+        syn::Item::Enum(syn::ItemEnum {
+            enum_token, ident, ..
+        }) => quote::quote! { #enum_token #ident },
+        syn::Item::Union(syn::ItemUnion {
+            union_token, ident, ..
+        }) => quote::quote! { #union_token #ident },
+        syn::Item::Trait(syn::ItemTrait {
+            trait_token, ident, ..
+        }) => quote::quote! { #trait_token #ident },
+        syn::Item::Type(syn::ItemType {
+            type_token, ident, ..
+        }) => quote::quote! { #type_token #ident },
+        syn::Item::Static(syn::ItemStatic {
+            static_token,
+            ident,
+            ..
+        }) => quote::quote! { #static_token #ident },
+        syn::Item::Mod(syn::ItemMod {
+            mod_token, ident, ..
+        }) => quote::quote! { #mod_token #ident },
+        syn::Item::Impl(syn::ItemImpl {
+            impl_token,
+            self_ty,
+            ..
+        }) => quote::quote! { #impl_token #self_ty },
+        syn::Item::Fn(syn::ItemFn { sig, .. }) => quote::quote! { #sig },
+        syn::Item::Const(syn::ItemConst {
+            const_token, ident, ..
+        }) => quote::quote! { #const_token #ident },
+        _ => quote::quote! { #item },
+    }
+}
+
+///Implements `rifgen::ExportedType` for the type when the types are checked
+fn exported_type_impl(
+    name: &syn::Ident,
+    generics: &syn::Generics,
+) -> Option<proc_macro2::TokenStream> {
+    if checked_languages().is_empty() {
+        return None;
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Some(quote::quote! {
+        impl #impl_generics ::rifgen::ExportedType for #name #ty_generics #where_clause {}
+    })
+}
+
+///The item followed by the errors, if any, so the item can still be used
+fn expand(item: proc_macro2::TokenStream, result: syn::Result<()>) -> TokenStream {
+    let errors = result.err().map(|error| error.to_compile_error());
+    let fin = quote::quote! {
+        #item
+        #errors
+    };
+    fin.into()
 }
@@ -0,0 +1,180 @@
+use std::path::Path;
+use syn::Attribute;
+
+///Start of the comments of the interface file listing the annotations
+const ANNOTATION_COMMENT: &str = "//rifgen annotation: ";
+
+///Annotation of `#[deprecated]` structs and methods
+const DEPRECATED: &str = "@Deprecated";
+
+///A Java annotation added with `#[generate_interface(annotate = "@Keep")]` on a method or
+/// `#[generate_interface_doc(annotate = "...")]` on a struct. `#[deprecated]` structs and methods
+/// are annotated with `@Deprecated`.
+///
+/// flapigen has no syntax for annotations so they're written in the interface file as attributes
+/// of the methods (or derives of the classes) and added to the generated Java code by
+/// callbacks registered with flapigen
+/// ```ignore
+/// let mut generator = flapigen::Generator::new(LanguageConfig::JavaConfig(config));
+/// for annotation in rifgen::foreign_annotations(&interface_file) {
+///     let method_annotation = annotation.clone();
+///     generator = generator
+///         .register_method_attribute_callback(annotation.attribute(), move |code, info| {
+///             method_annotation.annotate_method(code, info.method_name)
+///         })
+///         .register_class_attribute_callback(annotation.attribute(), move |code, class_name| {
+///             annotation.annotate_class(code, class_name)
+///         });
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignAnnotation {
+    attribute: String,
+    annotation: String,
+}
+
+impl ForeignAnnotation {
+    pub(crate) fn new(annotation: &str) -> ForeignAnnotation {
+        let mut attribute = String::from("rifgen_");
+        attribute.extend(annotation.trim_start_matches('@').chars().map(|it| {
+            if it.is_ascii_alphanumeric() {
+                it
+            } else {
+                '_'
+            }
+        }));
+        ForeignAnnotation {
+            attribute,
+            annotation: annotation.to_string(),
+        }
+    }
+
+    ///Name of the attribute in the interface file ie `rifgen_Keep` for `@Keep`
+    pub fn attribute(&self) -> &str {
+        &self.attribute
+    }
+
+    pub fn annotation(&self) -> &str {
+        &self.annotation
+    }
+
+    ///Comment of the interface file read by [`foreign_annotations`]
+    pub(crate) fn comment(&self) -> String {
+        format!(
+            "{}{} {}\n",
+            ANNOTATION_COMMENT, self.attribute, self.annotation
+        )
+    }
+
+    ///Adds the annotation before the declaration of the method in the generated Java class.
+    /// `method_name` is the name of the method in Java
+    pub fn annotate_method(&self, code: &mut Vec<u8>, method_name: &str) {
+        let call = format!(" {}(", method_name);
+        self.insert_before(code, |line| {
+            (line.starts_with("public ") || line.starts_with("protected "))
+                && line.contains(&call)
+                && !line.contains(" native ")
+        })
+    }
+
+    ///Adds the annotation before the declaration of the generated Java class
+    pub fn annotate_class(&self, code: &mut Vec<u8>, class_name: &str) {
+        self.insert_before(code, |line| {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            line.starts_with("public ")
+                && words
+                    .windows(2)
+                    .any(|it| it[0] == "class" && it[1].trim_end_matches('{') == class_name)
+        })
+    }
+
+    fn insert_before<F: Fn(&str) -> bool>(&self, code: &mut Vec<u8>, is_declaration: F) {
+        let text = String::from_utf8_lossy(code).to_string();
+        let mut result = String::with_capacity(text.len() + self.annotation.len());
+        for line in text.split_inclusive('\n') {
+            let trimmed = line.trim_start();
+            if is_declaration(trimmed) {
+                result.push_str(&line[..line.len() - trimmed.len()]);
+                result.push_str(&self.annotation);
+                result.push('\n');
+            }
+            result.push_str(line)
+        }
+        *code = result.into_bytes()
+    }
+}
+
+///The annotations of `annotate = "..."` along with `@Deprecated` for `#[deprecated]` items
+pub(crate) fn item_annotations(attrs: &[Attribute], mut annotations: Vec<String>) -> Vec<String> {
+    if attrs.iter().any(|it| it.path.is_ident("deprecated"))
+        && !annotations.iter().any(|it| it == DEPRECATED)
+    {
+        annotations.push(String::from(DEPRECATED))
+    }
+    annotations
+}
+
+///The annotations used in the interface file. Register a callback with flapigen for each
+/// of them. See [`ForeignAnnotation`]
+pub fn foreign_annotations<P: AsRef<Path>>(interface_file_path: P) -> Vec<ForeignAnnotation> {
+    let contents = std::fs::read_to_string(interface_file_path).unwrap_or_default();
+    contents
+        .lines()
+        .filter_map(|it| it.strip_prefix(ANNOTATION_COMMENT))
+        .filter_map(|it| it.split_once(' '))
+        .map(|(attribute, annotation)| ForeignAnnotation {
+            attribute: attribute.to_string(),
+            annotation: annotation.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::annotations::{item_annotations, ForeignAnnotation};
+
+    #[test]
+    fn deprecated() {
+        let item: syn::ItemFn =
+            syn::parse_str("#[deprecated(note = \"use bar\")] fn foo() {}").unwrap();
+        assert_eq!(
+            item_annotations(&item.attrs, vec![String::from("@Keep")]),
+            vec!["@Keep", "@Deprecated"]
+        );
+        assert_eq!(
+            item_annotations(&item.attrs, vec![String::from("@Deprecated")]),
+            vec!["@Deprecated"]
+        );
+        assert!(item_annotations(&[], vec![]).is_empty());
+    }
+
+    #[test]
+    fn annotations() {
+        let annotation = ForeignAnnotation::new("@SuppressWarnings(\"unused\")");
+        assert_eq!(annotation.attribute(), "rifgen_SuppressWarnings__unused__");
+        let mut code = b"package a;
+public final class Foo {
+    public final int getId() {
+        long ret = do_getId(mNativeObj);
+        return ret;
+    }
+    private static native int do_getId(long self);
+}"
+        .to_vec();
+        annotation.annotate_method(&mut code, "getId");
+        ForeignAnnotation::new("@Keep").annotate_class(&mut code, "Foo");
+        assert_eq!(
+            String::from_utf8(code).unwrap(),
+            "package a;
+@Keep
+public final class Foo {
+    @SuppressWarnings(\"unused\")
+    public final int getId() {
+        long ret = do_getId(mNativeObj);
+        return ret;
+    }
+    private static native int do_getId(long self);
+}"
+        );
+    }
+}
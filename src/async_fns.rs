@@ -0,0 +1,317 @@
+use crate::companion::arg_name;
+use inflector::Inflector;
+use rifgen_attributes_utils::closures::closure_type;
+use syn::__private::ToTokens;
+use syn::{FnArg, GenericArgument, PathArguments, ReturnType, Signature, Type};
+
+pub const ASYNC_WRAPPER_PREFIX: &str = "rifgen_async_";
+
+///An exported `async fn`. It's exported as a method taking a completion callback instead,
+/// which spawns the future on the executor set with `Generator::async_executor`
+#[derive(Debug)]
+pub struct AsyncMethod {
+    ///the Rust code of the method of the companion file calling the async method
+    pub wrapper: String,
+    ///the Rust code of the completion callback trait
+    pub callback: String,
+}
+
+///What the async method returns
+enum Output {
+    Unit,
+    Value(String),
+    ///`Result<(), E>`
+    UnitResult,
+    ///`Result<T, E>`
+    Result(String),
+}
+
+impl Output {
+    fn new(output: &ReturnType) -> Output {
+        let ty = match output {
+            ReturnType::Default => return Output::Unit,
+            ReturnType::Type(_, ty) => &**ty,
+        };
+        if is_unit(ty) {
+            return Output::Unit;
+        }
+        let value = |ty: &Type| ty.to_token_stream().to_string();
+        if let Type::Path(path) = ty {
+            let segment = path.path.segments.last().unwrap();
+            if let (true, PathArguments::AngleBracketed(args)) =
+                (segment.ident == "Result", &segment.arguments)
+            {
+                if let Some(GenericArgument::Type(ok)) = args.args.first() {
+                    return if is_unit(ok) {
+                        Output::UnitResult
+                    } else {
+                        Output::Result(value(ok))
+                    };
+                }
+            }
+        }
+        Output::Value(value(ty))
+    }
+}
+
+fn is_unit(ty: &Type) -> bool {
+    matches!(ty, Type::Tuple(val) if val.elems.is_empty())
+}
+
+///Name of the completion callback of `class_name::method_name` ie `ConnFetchCallback`
+pub fn callback_name(class_name: &str, method_name: &str) -> String {
+    format!("{}{}Callback", class_name, method_name.to_pascal_case())
+}
+
+impl AsyncMethod {
+    ///`is_clone` is whether the class implements `Clone` since the future can't borrow `self`.
+    /// Returns why it can't be exported otherwise
+    pub fn new(
+        class_name: &str,
+        signature: &Signature,
+        is_clone: bool,
+        executor: &str,
+    ) -> Result<AsyncMethod, String> {
+        let method_name = signature.ident.to_string();
+        if !signature.generics.params.is_empty() {
+            return Err(String::from("generic async methods aren't supported"));
+        }
+        let callback_name = callback_name(class_name, &method_name);
+        let mut params = vec![];
+        let mut prelude = vec![];
+        let mut args = vec![];
+        let mut call_on = class_name.to_string();
+        let mut separator = "::";
+        for arg in &signature.inputs {
+            let name = match arg {
+                FnArg::Typed(val) => arg_name(&val.pat, args.len()),
+                FnArg::Receiver(_) => String::new(),
+            };
+            match arg {
+                FnArg::Receiver(receiver) => match (&receiver.reference, &receiver.mutability) {
+                    (_, Some(_)) => return Err(String::from(
+                        "async methods can't take `self` mutably as the future outlives the call",
+                    )),
+                    (Some(_), None) if !is_clone => {
+                        return Err(format!(
+                            "async methods taking `&self` need {} to implement Clone as the \
+                            future outlives the call",
+                            class_name
+                        ))
+                    }
+                    (Some(_), None) => {
+                        params.push(String::from("&self"));
+                        prelude.push(String::from("let this = self.clone();"));
+                        call_on = String::from("this");
+                        separator = ".";
+                    }
+                    (None, None) => {
+                        params.push(String::from("self"));
+                        call_on = String::from("self");
+                        separator = ".";
+                    }
+                },
+                FnArg::Typed(typed) if closure_type(&typed.ty).is_some() => {
+                    return Err(String::from("async methods can't take closures"))
+                }
+                FnArg::Typed(typed) => match &*typed.ty {
+                    Type::Reference(reference) if reference.mutability.is_some() => {
+                        return Err(String::from(
+                            "async methods can't take `&mut` arguments as the future outlives \
+                            the call",
+                        ))
+                    }
+                    Type::Reference(reference)
+                        if matches!(
+                            &*reference.elem,
+                            Type::TraitObject(_) | Type::ImplTrait(_)
+                        ) =>
+                    {
+                        return Err(String::from(
+                            "async methods can't take references to trait objects as they can't \
+                            be copied into the future",
+                        ))
+                    }
+                    //owned copies are moved into the future. `str` and slices are copied into a
+                    //`String` and a `Vec`, other types should implement `Clone`
+                    Type::Reference(reference) => {
+                        let elem = reference.elem.to_token_stream();
+                        params.push(format!("{}: &{}", name, elem));
+                        prelude.push(match &*reference.elem {
+                            Type::Slice(_) => format!("let {} = {}.to_vec();", name, name),
+                            Type::Path(path) if path.path.is_ident("str") => {
+                                format!("let {} = {}.to_string();", name, name)
+                            }
+                            _ => format!("let {} = <{} as Clone>::clone({});", name, elem, name),
+                        });
+                        args.push(format!("&{}", name));
+                    }
+                    Type::Path(_) if typed.pat.to_token_stream().to_string() == "self" => {
+                        return Err(String::from(
+                            "async methods taking `self` as a smart pointer aren't supported",
+                        ))
+                    }
+                    ty => {
+                        params.push(format!("{}: {}", name, ty.to_token_stream()));
+                        args.push(name);
+                    }
+                },
+            }
+        }
+        params.push(format!("callback: Box<dyn {}>", callback_name));
+        let call = format!(
+            "{}{}{}({}).await",
+            call_on,
+            separator,
+            method_name,
+            args.join(", ")
+        );
+        let output = Output::new(&signature.output);
+        let (on_success, body) = match &output {
+            Output::Unit => (
+                String::from("fn on_success(&self);"),
+                format!("{};\n            callback.on_success();", call),
+            ),
+            Output::Value(ty) => (
+                format!("fn on_success(&self, value: {});", ty),
+                format!("callback.on_success({});", call),
+            ),
+            Output::UnitResult | Output::Result(_) => {
+                let (on_success, success) = match &output {
+                    Output::Result(ty) => (
+                        format!("fn on_success(&self, value: {});", ty),
+                        "Ok(value) => callback.on_success(value)",
+                    ),
+                    _ => (
+                        String::from("fn on_success(&self);"),
+                        "Ok(_) => callback.on_success()",
+                    ),
+                };
+                (
+                    on_success,
+                    format!(
+                        "match {} {{
+                {},
+                Err(error) => callback.on_error(error.to_string()),
+            }}",
+                        call, success
+                    ),
+                )
+            }
+        };
+        let callback = format!(
+            "
+///Completion callback of `{class}::{method}`
+pub trait {callback}: Send {{
+    ///Called with the output of `{class}::{method}` once it completes
+    {on_success}
+    ///Called with the message of the error returned by `{class}::{method}`
+    fn on_error(&self, error: String);
+}}
+",
+            class = class_name,
+            method = method_name,
+            callback = callback_name,
+            on_success = on_success
+        );
+        let prelude = prelude
+            .iter()
+            .map(|it| format!("\n        {}", it))
+            .collect::<String>();
+        let wrapper = format!(
+            "
+    pub fn {prefix}{method}({params}) {{{prelude}
+        {executor}(async move {{
+            {body}
+        }})
+    }}
+",
+            prefix = ASYNC_WRAPPER_PREFIX,
+            method = method_name,
+            params = params.join(", "),
+            prelude = prelude,
+            executor = executor,
+            body = body
+        );
+        Ok(AsyncMethod { wrapper, callback })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::async_fns::AsyncMethod;
+
+    #[test]
+    fn async_method() {
+        let signature = |it: &str| syn::parse_str::<syn::Signature>(it).unwrap();
+        let method = AsyncMethod::new(
+            "Conn",
+            &signature("async fn fetch(&self, url: &str, _: u8) -> Result<Vec<u8>, NetErr>"),
+            true,
+            "crate::spawn",
+        )
+        .unwrap();
+        assert_eq!(
+            method.wrapper,
+            "
+    pub fn rifgen_async_fetch(&self, url: &str, a1: u8, callback: Box<dyn ConnFetchCallback>) {
+        let this = self.clone();
+        let url = url.to_string();
+        crate::spawn(async move {
+            match this.fetch(&url, a1).await {
+                Ok(value) => callback.on_success(value),
+                Err(error) => callback.on_error(error.to_string()),
+            }
+        })
+    }
+"
+        );
+        assert!(method
+            .callback
+            .contains("pub trait ConnFetchCallback: Send {"));
+        assert!(method
+            .callback
+            .contains("fn on_success(&self, value: Vec < u8 >);"));
+        let method = AsyncMethod::new(
+            "Conn",
+            &signature("async fn send(&self, data: &[u8], to: &Peer)"),
+            true,
+            "spawn",
+        )
+        .unwrap();
+        assert!(method.wrapper.contains(
+            "
+        let data = data.to_vec();
+        let to = <Peer as Clone>::clone(to);"
+        ));
+        assert!(AsyncMethod::new(
+            "Conn",
+            &signature("async fn notify(&self, listener: &dyn Listener)"),
+            true,
+            "spawn"
+        )
+        .is_err());
+        let method = AsyncMethod::new(
+            "Conn",
+            &signature("async fn connect(host: String)"),
+            false,
+            "spawn",
+        )
+        .unwrap();
+        assert!(method
+            .wrapper
+            .contains("Conn::connect(host).await;\n            callback.on_success();"));
+        assert!(
+            AsyncMethod::new("Conn", &signature("async fn close(&self)"), false, "spawn")
+                .unwrap_err()
+                .contains("need Conn to implement Clone")
+        );
+        assert!(AsyncMethod::new(
+            "Conn",
+            &signature("async fn reset(&mut self)"),
+            true,
+            "spawn"
+        )
+        .is_err());
+    }
+}
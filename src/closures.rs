@@ -0,0 +1,231 @@
+use crate::companion::arg_name;
+use inflector::Inflector;
+use rifgen_attributes_utils::closures::{closure_type, ClosureKind, ClosureType};
+use syn::__private::ToTokens;
+use syn::{FnArg, Signature, Type};
+
+pub const CLOSURE_WRAPPER_PREFIX: &str = "rifgen_closure_";
+
+///An exported method taking closures. It's exported as a method taking callbacks instead,
+/// which are turned into the closures
+#[derive(Debug)]
+pub struct ClosureMethod {
+    ///the Rust code of the method of the companion file calling the method
+    pub wrapper: String,
+    ///the Rust code of the callback of each closure. Closures with the same signature
+    /// have the same callback
+    pub callbacks: Vec<String>,
+}
+
+///Name of the callback standing for the closure ie `I32ToBoolFn` for `Fn(i32) -> bool`
+fn callback_name(closure: &ClosureType) -> String {
+    let mut name = String::new();
+    for input in &closure.inputs {
+        name.push_str(&type_name(input))
+    }
+    if name.is_empty() {
+        name.push_str("Unit")
+    }
+    if let Some(output) = closure.output {
+        name.push_str("To");
+        name.push_str(&type_name(output))
+    }
+    name.push_str("Fn");
+    name
+}
+
+///`VecU8` for `Vec<u8>`
+fn type_name(ty: &Type) -> String {
+    ty.to_token_stream()
+        .to_string()
+        .split(|it: char| !it.is_alphanumeric())
+        .map(|it| it.to_pascal_case())
+        .collect()
+}
+
+///The trait implemented by the foreign callback and its conversion to the closure
+fn callback(name: &str, closure: &ClosureType) -> String {
+    let inputs = closure
+        .inputs
+        .iter()
+        .map(|it| it.to_token_stream().to_string())
+        .collect::<Vec<_>>();
+    let params = inputs
+        .iter()
+        .enumerate()
+        .map(|(index, ty)| format!("a{}: {}", index, ty))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let args = (0..inputs.len())
+        .map(|it| format!("a{}", it))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let output = closure
+        .output
+        .map(|it| format!(" -> {}", it.to_token_stream()))
+        .unwrap_or_default();
+    let signature = format!("Fn({}){}", inputs.join(", "), output);
+    format!(
+        "
+///Callback standing for `{signature}` closures
+pub trait {name}: Send {{
+    fn call(&self{separator}{params}){output};
+}}
+
+impl dyn {name} {{
+    ///The closure calling this callback
+    pub fn into_closure(self: Box<Self>) -> impl {signature} + Send {{
+        move |{params}| self.call({args})
+    }}
+}}
+",
+        signature = signature,
+        name = name,
+        separator = if params.is_empty() { "" } else { ", " },
+        params = params,
+        output = output,
+        args = args
+    )
+}
+
+impl ClosureMethod {
+    ///`None` when the method doesn't take closures. Returns why it can't be exported otherwise
+    pub fn new(class_name: &str, signature: &Signature) -> Option<Result<ClosureMethod, String>> {
+        let takes_closures = signature.inputs.iter().any(|it| match it {
+            FnArg::Typed(val) => closure_type(&val.ty).is_some(),
+            FnArg::Receiver(_) => false,
+        });
+        if !takes_closures {
+            return None;
+        }
+        Some(ClosureMethod::wrap(class_name, signature))
+    }
+
+    fn wrap(class_name: &str, signature: &Signature) -> Result<ClosureMethod, String> {
+        let method_name = signature.ident.to_string();
+        if !signature.generics.params.is_empty() {
+            return Err(String::from(
+                "generic methods taking closures aren't supported",
+            ));
+        }
+        let mut params = vec![];
+        let mut args = vec![];
+        let mut callbacks = vec![];
+        let mut call_on = format!("{}::", class_name);
+        for arg in &signature.inputs {
+            let typed = match arg {
+                FnArg::Receiver(receiver) => {
+                    params.push(String::from(
+                        match (&receiver.reference, &receiver.mutability) {
+                            (Some(_), Some(_)) => "&mut self",
+                            (Some(_), None) => "&self",
+                            (None, _) => "self",
+                        },
+                    ));
+                    call_on = String::from("self.");
+                    continue;
+                }
+                FnArg::Typed(val) => val,
+            };
+            let name = arg_name(&typed.pat, args.len());
+            if name == "self" {
+                return Err(String::from(
+                    "methods taking `self` as a smart pointer can't take closures",
+                ));
+            }
+            let closure = match closure_type(&typed.ty) {
+                Some(val) => val,
+                None => {
+                    params.push(format!("{}: {}", name, typed.ty.to_token_stream()));
+                    args.push(name);
+                    continue;
+                }
+            };
+            if closure.is_sync {
+                return Err(String::from(
+                    "closures can't be Sync as foreign callbacks aren't",
+                ));
+            }
+            let callback_name = callback_name(&closure);
+            params.push(format!("{}: Box<dyn {}>", name, callback_name));
+            args.push(match closure.kind {
+                ClosureKind::Impl => format!("{}.into_closure()", name),
+                ClosureKind::Boxed => format!("Box::new({}.into_closure())", name),
+                ClosureKind::Ref => format!("&{}.into_closure()", name),
+                ClosureKind::RefMut => format!("&mut {}.into_closure()", name),
+            });
+            callbacks.push(callback(&callback_name, &closure));
+        }
+        let wrapper = format!(
+            "
+    pub fn {prefix}{method}({params}){output} {{
+        {call_on}{method}({args})
+    }}
+",
+            prefix = CLOSURE_WRAPPER_PREFIX,
+            method = method_name,
+            params = params.join(", "),
+            output = match &signature.output {
+                syn::ReturnType::Default => String::new(),
+                syn::ReturnType::Type(_, ty) => format!(" -> {}", ty.to_token_stream()),
+            },
+            call_on = call_on,
+            args = args.join(", ")
+        );
+        Ok(ClosureMethod { wrapper, callbacks })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::closures::ClosureMethod;
+
+    #[test]
+    fn closure_method() {
+        let signature = |it: &str| syn::parse_str::<syn::Signature>(it).unwrap();
+        let method = ClosureMethod::new(
+            "List",
+            &signature(
+                "fn filter(&self, predicate: impl Fn(i32) -> bool, on_done: &mut dyn FnMut()) -> usize",
+            ),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            method.wrapper,
+            "
+    pub fn rifgen_closure_filter(&self, predicate: Box<dyn I32ToBoolFn>, on_done: Box<dyn UnitFn>) -> usize {
+        self.filter(predicate.into_closure(), &mut on_done.into_closure())
+    }
+"
+        );
+        assert_eq!(
+            method.callbacks[0],
+            "
+///Callback standing for `Fn(i32) -> bool` closures
+pub trait I32ToBoolFn: Send {
+    fn call(&self, a0: i32) -> bool;
+}
+
+impl dyn I32ToBoolFn {
+    ///The closure calling this callback
+    pub fn into_closure(self: Box<Self>) -> impl Fn(i32) -> bool + Send {
+        move |a0: i32| self.call(a0)
+    }
+}
+"
+        );
+        assert!(method.callbacks[1].contains("pub trait UnitFn: Send {\n    fn call(&self);"));
+        let method = ClosureMethod::new(
+            "List",
+            &signature("fn for_each(_: Box<dyn Fn(Vec<u8>, &str) + Send>)"),
+        )
+        .unwrap()
+        .unwrap();
+        assert!(method
+            .wrapper
+            .contains("List::for_each(Box::new(a0.into_closure()))"));
+        assert!(method.callbacks[0].contains("pub trait VecU8StrFn: Send {"));
+        assert!(ClosureMethod::new("List", &signature("fn len(&self) -> usize")).is_none());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::enums::SharedOwnership;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use syn::Pat;
+
+//helper methods added to exported types
+
+pub const HASH_CODE_SHIM: &str = "rifgen_hash_code";
+pub const DEBUG_STRING_SHIM: &str = "rifgen_to_string";
+pub const SHARED_CONSTRUCTOR_PREFIX: &str = "rifgen_shared_";
+pub const VALUE_SHIM: &str = "rifgen_value";
+pub const FROM_VALUE_SHIM: &str = "rifgen_from_value";
+///Suffix of the class converting the variants of an enum to their values ie `StatusValues`
+pub const VALUES_CLASS_SUFFIX: &str = "Values";
+
+///Rust code which the interface file depends on but isn't written by the user.\
+/// It's written to a separate file which should be included in the crate, in the same module
+/// as the interface file
+/// ```ignore
+/// include!(concat!(env!("OUT_DIR"), "/glue_shims.rs"));
+/// ```
+#[derive(Debug, Default)]
+pub struct CompanionFile {
+    code: String,
+}
+
+impl CompanionFile {
+    ///Adds an `impl` block with the given methods to `type_name`
+    pub fn add_impl(&mut self, type_name: &str, methods: Vec<String>) {
+        if methods.is_empty() {
+            return;
+        }
+        self.code.push_str("\nimpl ");
+        self.code.push_str(type_name);
+        self.code.push_str(" {");
+        for method in methods {
+            self.code.push_str(&method);
+        }
+        self.code.push_str("}\n");
+    }
+
+    ///Adds items such as traits
+    pub fn add_code(&mut self, code: &str) {
+        self.code.push_str(code);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty()
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) {
+        let mut file = File::create(path).expect("Unable to write to disk");
+        file.write_all(b"//Automatically generated by rifgen\n")
+            .and_then(|_| file.write_all(self.code.as_bytes()))
+            .expect("Unable to write to disk");
+    }
+}
+
+///Hash of the value using the standard hasher, truncated to fit `hashCode`
+pub fn hash_code_shim() -> String {
+    format!(
+        "
+    pub fn {}(&self) -> i32 {{
+        use std::hash::{{Hash, Hasher}};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish() as i32
+    }}
+",
+        HASH_CODE_SHIM
+    )
+}
+
+///`toString` for types implementing only `Debug`
+pub fn debug_string_shim() -> String {
+    format!(
+        "
+    pub fn {}(&self) -> String {{
+        format!(\"{{:?}}\", self)
+    }}
+",
+        DEBUG_STRING_SHIM
+    )
+}
+
+///Conversions of the variants of an enum to their values as `value_type` and back.
+/// Unknown values are errors
+pub fn enum_value_shims(enum_name: &str, value_type: &str, variants: &[&str]) -> Vec<String> {
+    let arms = variants
+        .iter()
+        .map(|it| {
+            format!(
+                "
+            value if value == {enum_name}::{variant} as {value_type} => Ok({enum_name}::{variant}),",
+                enum_name = enum_name,
+                variant = it,
+                value_type = value_type
+            )
+        })
+        .collect::<String>();
+    vec![
+        format!(
+            "
+    pub fn {}(variant: {}) -> {} {{
+        variant as {}
+    }}
+",
+            VALUE_SHIM, enum_name, value_type, value_type
+        ),
+        format!(
+            "
+    pub fn {}(value: {value_type}) -> Result<{enum_name}, String> {{
+        match value {{{arms}
+            _ => Err(format!(\"Invalid value for {enum_name}: {{}}\", value)),
+        }}
+    }}
+",
+            FROM_VALUE_SHIM,
+            value_type = value_type,
+            enum_name = enum_name,
+            arms = arms
+        ),
+    ]
+}
+
+///Name of an argument of the wrappers calling the exported methods. Patterns can't be used
+/// in the call so they're named `a0`, `a1`, ... by their position
+pub fn arg_name(pat: &Pat, index: usize) -> String {
+    match pat {
+        Pat::Ident(val) if val.subpat.is_none() && val.by_ref.is_none() => val.ident.to_string(),
+        _ => format!("a{}", index),
+    }
+}
+
+///Constructor returning the value of `constructor` in the shared pointer.\
+/// `args` holds the name and type of each argument
+pub fn shared_constructor_shim(
+    shared: SharedOwnership,
+    class_name: &str,
+    constructor: &str,
+    generics: &str,
+    args: &[(String, String)],
+) -> String {
+    let params = args
+        .iter()
+        .map(|(name, ty)| format!("{}: {}", name, ty))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let call = format!(
+        "{}::{}({})",
+        class_name,
+        constructor,
+        args.iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    format!(
+        "
+    pub fn {}{}{}({}) -> {} {{
+        {}
+    }}
+",
+        SHARED_CONSTRUCTOR_PREFIX,
+        constructor,
+        generics,
+        params,
+        shared.full_type(class_name),
+        shared.wrap_value(&call)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::companion::shared_constructor_shim;
+    use crate::enums::SharedOwnership;
+
+    #[test]
+    fn shared_constructor() {
+        let args = vec![
+            (String::from("a0"), String::from("u32")),
+            (String::from("a1"), String::from("T")),
+        ];
+        assert_eq!(
+            shared_constructor_shim(SharedOwnership::ArcMutex, "Foo", "new", "<T: Into<u32>>", &args),
+            "
+    pub fn rifgen_shared_new<T: Into<u32>>(a0: u32, a1: T) -> std::sync::Arc<std::sync::Mutex<Foo>> {
+        std::sync::Arc::new(std::sync::Mutex::new(Foo::new(a0, a1)))
+    }
+"
+        );
+        assert_eq!(
+            shared_constructor_shim(SharedOwnership::RcRefCell, "Foo", "empty", "", &[]),
+            "
+    pub fn rifgen_shared_empty() -> std::rc::Rc<std::cell::RefCell<Foo>> {
+        std::rc::Rc::new(std::cell::RefCell::new(Foo::empty()))
+    }
+"
+        );
+    }
+}
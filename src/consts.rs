@@ -0,0 +1,104 @@
+use syn::__private::ToTokens;
+use syn::{GenericArgument, Ident, PathArguments, Type};
+
+pub const CONST_GETTER_PREFIX: &str = "rifgen_const_";
+
+///Name of the class holding the exported consts which aren't associated with a type
+pub const DEFAULT_CONSTANTS_CLASS: &str = "Constants";
+
+///Static getter of an exported const since flapigen can't export fields.
+/// It's named like the getter ie `get_max_size` for `MAX_SIZE`
+pub struct ConstGetter {
+    pub name: String,
+    ///the Rust code of the method of the companion file returning the const
+    pub shim: String,
+}
+
+impl ConstGetter {
+    ///`class_name` is the type of associated consts
+    pub fn new(class_name: Option<&str>, ident: &Ident, ty: &Type) -> ConstGetter {
+        let const_name = ident.to_string();
+        let getter_name = const_name.to_lowercase();
+        let mut ty = ty.clone();
+        static_lifetimes(&mut ty);
+        let path = match class_name {
+            Some(class_name) => format!("{}::{}", class_name, const_name),
+            None => const_name,
+        };
+        ConstGetter {
+            name: format!("get_{}", getter_name),
+            shim: format!(
+                "
+    pub fn {}{}() -> {} {{
+        {}
+    }}
+",
+                CONST_GETTER_PREFIX,
+                getter_name,
+                ty.to_token_stream(),
+                path
+            ),
+        }
+    }
+}
+
+///Elided lifetimes of consts are `'static` but the getters take no references to elide them
+fn static_lifetimes(ty: &mut Type) {
+    match ty {
+        Type::Reference(val) => {
+            if val.lifetime.is_none() {
+                val.lifetime = syn::parse_str("'static").ok()
+            }
+            static_lifetimes(&mut val.elem)
+        }
+        Type::Slice(val) => static_lifetimes(&mut val.elem),
+        Type::Array(val) => static_lifetimes(&mut val.elem),
+        Type::Tuple(val) => val.elems.iter_mut().for_each(static_lifetimes),
+        Type::Path(val) => val.path.segments.iter_mut().for_each(|it| {
+            if let PathArguments::AngleBracketed(args) = &mut it.arguments {
+                args.args.iter_mut().for_each(|it| {
+                    if let GenericArgument::Type(ty) = it {
+                        static_lifetimes(ty)
+                    }
+                })
+            }
+        }),
+        _ => {}
+    }
+}
+
+///The class holding the consts which aren't associated with a type, when it isn't exported
+pub fn constants_class(class_name: &str) -> String {
+    format!(
+        "
+///Holds the exported consts which aren't associated with a type
+pub struct {};
+",
+        class_name
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consts::ConstGetter;
+
+    #[test]
+    fn const_getter() {
+        let item: syn::ItemConst = syn::parse_str("pub const MAX_SIZE: usize = 10;").unwrap();
+        let getter = ConstGetter::new(Some("Buffer"), &item.ident, &item.ty);
+        assert_eq!(getter.name, "get_max_size");
+        assert_eq!(
+            getter.shim,
+            "
+    pub fn rifgen_const_max_size() -> usize {
+        Buffer::MAX_SIZE
+    }
+"
+        );
+        let item: syn::ItemConst = syn::parse_str("const VERSION: &str = \"1.0\";").unwrap();
+        let getter = ConstGetter::new(None, &item.ident, &item.ty);
+        assert!(getter
+            .shim
+            .contains("pub fn rifgen_const_version() -> & 'static str {\n        VERSION\n"));
+    }
+}
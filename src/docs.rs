@@ -0,0 +1,630 @@
+use crate::types_structs::TYPE_CASE;
+use crate::{Language, TypeCases};
+use inflector::Inflector;
+use syn::__private::ToTokens;
+use syn::parse::Parser;
+use syn::{Attribute, Lit, Meta, NestedMeta};
+
+///Sections of rustdoc which are converted to block tags ie `# Arguments` to `@param`
+#[derive(Copy, Clone, PartialEq)]
+enum Section {
+    Description,
+    Arguments,
+    Returns,
+    Errors,
+    Deprecated,
+}
+
+///Doc comments of an item as `# [doc = "..."]`.\
+/// The docs of `#[doc(hidden)]` items are left out and `#[deprecated]` is added as a
+/// `# Deprecated` section
+pub(crate) fn item_docs(attrs: &[Attribute]) -> Vec<String> {
+    let mut docs = if is_doc_hidden(attrs) {
+        vec![]
+    } else {
+        attrs
+            .iter()
+            .filter(|it| it.path.is_ident("doc"))
+            .map(|it| it.to_token_stream().to_string())
+            .collect::<Vec<_>>()
+    };
+    if let Some(note) = deprecation_note(attrs) {
+        if !docs.is_empty() {
+            docs.push(String::from("# [doc = \"\"]"))
+        }
+        docs.push(String::from("# [doc = \" # Deprecated\"]"));
+        if !note.is_empty() {
+            docs.push(format!("# [doc = {:?}]", format!(" {}", note)))
+        }
+    }
+    docs
+}
+
+///`#[doc(hidden)]`
+pub(crate) fn is_doc_hidden(attrs: &[Attribute]) -> bool {
+    attrs
+        .iter()
+        .filter(|it| it.path.is_ident("doc"))
+        .filter_map(|it| match it.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .any(|it| matches!(it, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("hidden")))
+}
+
+///The note of `#[deprecated(since = "...", note = "...")]` or `#[deprecated = "..."]`
+fn deprecation_note(attrs: &[Attribute]) -> Option<String> {
+    let attr = attrs.iter().find(|it| it.path.is_ident("deprecated"))?;
+    let string = |lit: &Lit| match lit {
+        Lit::Str(val) => Some(val.value()),
+        _ => None,
+    };
+    let (since, note) = match attr.parse_meta().ok()? {
+        Meta::NameValue(val) => (None, string(&val.lit)),
+        Meta::List(list) => {
+            let arg = |name: &str| {
+                list.nested.iter().find_map(|it| match it {
+                    NestedMeta::Meta(Meta::NameValue(val)) if val.path.is_ident(name) => {
+                        string(&val.lit)
+                    }
+                    _ => None,
+                })
+            };
+            (arg("since"), arg("note"))
+        }
+        Meta::Path(_) => (None, None),
+    };
+    Some(match (since, note) {
+        (Some(since), Some(note)) => format!("As of {}, {}", since, note),
+        (Some(since), None) => format!("As of {}", since),
+        (None, Some(note)) => note,
+        (None, None) => String::new(),
+    })
+}
+
+///Converts the rustdoc conventions of doc comments (held as `# [doc = "..."]`) to Javadoc or Doxygen.\
+/// Doc attributes without text such as `#[doc(hidden)]` are kept as they are
+pub(crate) fn translate_docs(docs: &[String], language: Language) -> Vec<String> {
+    let mut result = vec![];
+    let mut lines = vec![];
+    for doc in docs {
+        match doc_text(doc) {
+            Some(text) => lines.extend(text.split('\n').map(String::from)),
+            None => result.push(doc.to_string()),
+        }
+    }
+    if lines.is_empty() {
+        return result;
+    }
+    let translated = DocTranslator::new(language).translate(&unindent(lines));
+    result.extend(
+        translated
+            .into_iter()
+            .map(|it| format!("# [doc = {:?}]", format!(" {}", it).trim_end())),
+    );
+    result
+}
+
+///Text of `# [doc = "..."]`
+fn doc_text(doc: &str) -> Option<String> {
+    let attrs = syn::Attribute::parse_outer.parse_str(doc).ok()?;
+    match attrs.first()?.parse_meta().ok()? {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(val),
+            ..
+        }) => Some(val.value()),
+        _ => None,
+    }
+}
+
+///Removes the indentation common to all the lines ie the space after `///`
+fn unindent(lines: Vec<String>) -> Vec<String> {
+    let indent = lines
+        .iter()
+        .filter(|it| !it.trim().is_empty())
+        .map(|it| it.len() - it.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    lines
+        .into_iter()
+        .map(|it| it.get(indent..).unwrap_or_default().trim_end().to_string())
+        .collect()
+}
+
+struct DocTranslator {
+    language: Language,
+    description: Vec<String>,
+    ///`@param`, `@return` and `@throws` which should come after the description
+    tags: Vec<String>,
+    section: Section,
+    ///whether the first line of the section was read
+    section_started: bool,
+    ///`Some(is_rust)` in a code block
+    code_block: Option<bool>,
+    paragraph_break: bool,
+}
+
+impl DocTranslator {
+    fn new(language: Language) -> DocTranslator {
+        DocTranslator {
+            language,
+            description: vec![],
+            tags: vec![],
+            section: Section::Description,
+            section_started: false,
+            code_block: None,
+            paragraph_break: false,
+        }
+    }
+
+    fn translate(mut self, lines: &[String]) -> Vec<String> {
+        for line in lines {
+            self.add_line(line)
+        }
+        if self.code_block.is_some() {
+            self.end_code_block()
+        }
+        let mut result = self.description;
+        if !self.tags.is_empty() {
+            if !result.is_empty() {
+                result.push(String::new())
+            }
+            result.append(&mut self.tags)
+        }
+        result
+    }
+
+    fn add_line(&mut self, line: &str) {
+        let trimmed = line.trim();
+        if let Some(is_rust) = self.code_block {
+            if trimmed.starts_with("```") {
+                return self.end_code_block();
+            }
+            //lines hidden by rustdoc
+            if is_rust && (trimmed == "#" || trimmed.starts_with("# ")) {
+                return;
+            }
+            let line = line.replace("*/", "* /");
+            return self.push(line);
+        }
+        if let Some(info) = trimmed.strip_prefix("```") {
+            self.code_block = Some(is_rust_code(info));
+            let start = match self.language {
+                Language::Java => "<pre>{@code",
+                Language::Cpp => "\\code",
+            };
+            return self.push(start.to_string());
+        }
+        if let Some(title) = heading(trimmed) {
+            self.section = match title.to_lowercase().as_str() {
+                "arguments" | "parameters" => Section::Arguments,
+                "returns" | "return" => Section::Returns,
+                "errors" => Section::Errors,
+                "deprecated" => Section::Deprecated,
+                _ => Section::Description,
+            };
+            self.section_started = false;
+            self.paragraph_break = false;
+            //the note, if any, is added to the tag
+            if self.section == Section::Deprecated {
+                self.tags.push(match self.language {
+                    Language::Java => String::from("@deprecated"),
+                    Language::Cpp => String::from("\\deprecated"),
+                });
+                self.section_started = true;
+            }
+            if self.section == Section::Description {
+                let title = self.inline(title);
+                self.description.push(match self.language {
+                    Language::Java => format!("<h2>{}</h2>", title),
+                    Language::Cpp => format!("\\par {}", title),
+                })
+            }
+            return;
+        }
+        if trimmed.is_empty() {
+            self.paragraph_break = true;
+            return;
+        }
+        let tag = match self.language {
+            Language::Java => '@',
+            Language::Cpp => '\\',
+        };
+        match self.section {
+            Section::Description => {
+                if self.paragraph_break && !self.description.is_empty() {
+                    self.description.push(match self.language {
+                        Language::Java => String::from("<p>"),
+                        Language::Cpp => String::new(),
+                    })
+                }
+                let line = self.inline(line);
+                self.description.push(line)
+            }
+            Section::Arguments => match argument(trimmed) {
+                Some((name, description)) => {
+                    let description = self.inline(description);
+                    self.tags
+                        .push(format!("{}param {} {}", tag, name, description))
+                }
+                None => self.continue_tag(trimmed),
+            },
+            Section::Returns if !self.section_started => {
+                let line = self.inline(trimmed);
+                self.tags.push(format!("{}return {}", tag, line))
+            }
+            //Doxygen's \throws expects the name of the exception
+            Section::Errors if !self.section_started => {
+                let line = self.inline(trimmed);
+                match self.language {
+                    Language::Java => self.tags.push(format!("@throws Exception {}", line)),
+                    Language::Cpp => {
+                        self.tags.push(String::from("\\par Errors"));
+                        self.tags.push(line)
+                    }
+                }
+            }
+            Section::Returns | Section::Errors | Section::Deprecated => self.continue_tag(trimmed),
+        }
+        self.section_started = true;
+        self.paragraph_break = false;
+    }
+
+    ///Appends the line to the last block tag
+    fn continue_tag(&mut self, line: &str) {
+        let line = self.inline(line);
+        match self.tags.last_mut() {
+            Some(last) => {
+                last.push(' ');
+                last.push_str(&line)
+            }
+            None => self.description.push(line),
+        }
+    }
+
+    fn push(&mut self, line: String) {
+        match self.section {
+            Section::Description => self.description.push(line),
+            _ => self.tags.push(line),
+        }
+    }
+
+    fn end_code_block(&mut self) {
+        self.code_block = None;
+        let end = match self.language {
+            Language::Java => "}</pre>",
+            Language::Cpp => "\\endcode",
+        };
+        self.push(end.to_string())
+    }
+
+    ///Converts inline code and intra-doc links and escapes the rest of the text
+    fn inline(&self, text: &str) -> String {
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut result = String::with_capacity(text.len());
+        let mut index = 0;
+        while index < chars.len() {
+            match chars[index] {
+                '`' => {
+                    if let Some(end) = find(&chars, index + 1, '`') {
+                        let code = chars[index + 1..end].iter().collect::<String>();
+                        result.push_str(&self.code(&code));
+                        index = end + 1;
+                        continue;
+                    }
+                }
+                '[' => {
+                    if let Some((link, next)) = self.link(&chars, index) {
+                        result.push_str(&link);
+                        index = next;
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+            self.escape(chars[index], &mut result);
+            index += 1;
+        }
+        result
+    }
+
+    fn escape(&self, char: char, out: &mut String) {
+        match (self.language, char) {
+            //`*/` would end the comment
+            (_, '/') if out.ends_with('*') => out.push_str(match self.language {
+                Language::Java => "&#47;",
+                Language::Cpp => " /",
+            }),
+            (Language::Java, '<') => out.push_str("&lt;"),
+            (Language::Java, '>') => out.push_str("&gt;"),
+            (Language::Java, '&') => out.push_str("&amp;"),
+            (Language::Java, '@') => out.push_str("&#64;"),
+            (Language::Cpp, '\\' | '@' | '<' | '>' | '&') => {
+                out.push('\\');
+                out.push(char)
+            }
+            _ => out.push(char),
+        }
+    }
+
+    fn code(&self, code: &str) -> String {
+        match self.language {
+            //`{@code}` needs balanced braces
+            Language::Java if code.matches('{').count() == code.matches('}').count() => {
+                format!("{{@code {}}}", code.replace("*/", "* /"))
+            }
+            Language::Java => {
+                let mut result = String::from("<code>");
+                code.chars().for_each(|it| self.escape(it, &mut result));
+                result.push_str("</code>");
+                result
+            }
+            Language::Cpp => format!("`{}`", code),
+        }
+    }
+
+    ///`[`Foo`]`, `[Foo::bar]`, `[text](Foo)` or `[text](https://...)` starting at `start`.
+    /// Returns the converted link and the index after it
+    fn link(&self, chars: &[char], start: usize) -> Option<(String, usize)> {
+        let close = find(chars, start + 1, ']')?;
+        let label = chars[start + 1..close].iter().collect::<String>();
+        if label.contains('[') {
+            return None;
+        }
+        if chars.get(close + 1) == Some(&'(') {
+            let end = find(chars, close + 2, ')')?;
+            let target = chars[close + 2..end].iter().collect::<String>();
+            let link = match intra_doc_path(&target) {
+                Some(path) => self.doc_link(&path, Some(&label)),
+                None => match self.language {
+                    Language::Java => format!("<a href=\"{}\">{}</a>", target, self.inline(&label)),
+                    Language::Cpp => format!("[{}]({})", self.inline(&label), target),
+                },
+            };
+            return Some((link, end + 1));
+        }
+        //reference style links aren't converted
+        if chars.get(close + 1) == Some(&'[') {
+            return None;
+        }
+        //plain words in brackets are only links if they look like types
+        let path = intra_doc_path(&label).filter(|it| {
+            label.starts_with('`') || it.contains("::") || it.starts_with(char::is_uppercase)
+        })?;
+        Some((self.doc_link(&path, None), close + 1))
+    }
+
+    ///`{@link Foo#bar}` for Java and `Foo::bar` for Doxygen which links it automatically
+    fn doc_link(&self, path: &str, label: Option<&str>) -> String {
+        let segments = path.split("::").collect::<Vec<_>>();
+        let label = label
+            .map(|it| it.trim_matches('`'))
+            .filter(|it| *it != path)
+            .map(|it| self.inline(it));
+        let (type_name, member) = match segments.as_slice() {
+            [.., type_name, member] if member.starts_with(char::is_lowercase) => {
+                (Some(*type_name), Some(*member))
+            }
+            [member] if member.starts_with(char::is_lowercase) => (None, Some(*member)),
+            [.., type_name] => (Some(*type_name), None),
+            [] => (None, None),
+        };
+        match self.language {
+            Language::Java => {
+                let mut target = type_name.unwrap_or_default().to_string();
+                if let Some(member) = member {
+                    target.push('#');
+                    target.push_str(&method_name(member))
+                }
+                match label {
+                    Some(label) => format!("{{@link {} {}}}", target, label),
+                    None => format!("{{@link {}}}", target),
+                }
+            }
+            Language::Cpp => match label {
+                Some(label) => label,
+                None => type_name
+                    .into_iter()
+                    .chain(member)
+                    .collect::<Vec<_>>()
+                    .join("::"),
+            },
+        }
+    }
+}
+
+fn find(chars: &[char], from: usize, char: char) -> Option<usize> {
+    chars
+        .iter()
+        .skip(from)
+        .position(|it| *it == char)
+        .map(|it| it + from)
+}
+
+///`# Title` up to `###### Title`
+fn heading(line: &str) -> Option<&str> {
+    let title = line.trim_start_matches('#');
+    let level = line.len() - title.len();
+    if (1..=6).contains(&level) && title.starts_with(' ') {
+        Some(title.trim())
+    } else {
+        None
+    }
+}
+
+///Code blocks are Rust code unless another language is given ie ```` ```text ````
+fn is_rust_code(info: &str) -> bool {
+    info.split(',').map(str::trim).all(|it| {
+        matches!(
+            it,
+            "" | "rust" | "ignore" | "no_run" | "should_panic" | "compile_fail"
+        ) || it.starts_with("edition")
+    })
+}
+
+///An item of the `# Arguments` list ie `` * `name` - description ``
+fn argument(line: &str) -> Option<(&str, &str)> {
+    let item = line
+        .strip_prefix("* ")
+        .or_else(|| line.strip_prefix("- "))
+        .or_else(|| line.strip_prefix("+ "))?
+        .trim_start();
+    let (name, rest) = match item.strip_prefix('`') {
+        Some(rest) => {
+            let end = rest.find('`')?;
+            (&rest[..end], &rest[end + 1..])
+        }
+        None => {
+            let end = item
+                .find(|it: char| !(it.is_alphanumeric() || it == '_'))
+                .unwrap_or(item.len());
+            item.split_at(end)
+        }
+    };
+    if name.is_empty() {
+        return None;
+    }
+    let description = rest
+        .trim_start()
+        .trim_start_matches(['-', ':', '–', '—'])
+        .trim_start();
+    Some((name, description))
+}
+
+///The path of an intra-doc link ie `Foo::bar` for ``[`crate::Foo::bar()`]``
+fn intra_doc_path(target: &str) -> Option<String> {
+    let mut path = target.trim().trim_matches('`');
+    //disambiguators such as `struct@Foo`
+    if let Some((_, rest)) = path.split_once('@') {
+        path = rest
+    }
+    path = path.trim_end_matches("()").trim_end_matches('!');
+    while let Some(rest) = ["crate::", "self::", "super::"]
+        .iter()
+        .find_map(|it| path.strip_prefix(it))
+    {
+        path = rest
+    }
+    let is_path = !path.is_empty()
+        && path.split("::").all(|segment| {
+            segment.starts_with(|it: char| it.is_alphabetic() || it == '_')
+                && segment.chars().all(|it| it.is_alphanumeric() || it == '_')
+        });
+    is_path.then(|| path.to_string())
+}
+
+fn method_name(name: &str) -> String {
+    unsafe {
+        match TYPE_CASE {
+            TypeCases::Default => name.to_string(),
+            TypeCases::CamelCase => name.to_camel_case(),
+            TypeCases::SnakeCase => name.to_snake_case(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::docs::{is_doc_hidden, item_docs, translate_docs};
+    use crate::Language;
+
+    fn translate(doc: &str, language: Language) -> Vec<String> {
+        let docs = doc
+            .lines()
+            .map(|it| format!("# [doc = {:?}]", it))
+            .collect::<Vec<_>>();
+        translate_docs(&docs, language)
+            .iter()
+            .map(|it| {
+                syn::parse_str::<syn::LitStr>(&it["# [doc = ".len()..it.len() - 1])
+                    .unwrap()
+                    .value()
+            })
+            .collect()
+    }
+
+    const DOC: &str =
+        " Adds an item to [`Inventory`] if `count > 0`, see [`Inventory::remove_item`].
+ Docs are at [the site](https://example.com).
+
+ # Arguments
+ * `id` - the id of the item
+ * `count`: how many
+   of them
+
+ # Returns
+ The new total
+
+ # Errors
+ If the inventory is full
+
+ # Examples
+ ```
+ # let inventory = Inventory::default();
+ inventory.add(1, 2);
+ ```";
+
+    #[test]
+    fn javadoc() {
+        assert_eq!(
+            translate(DOC, Language::Java),
+            vec![
+                " Adds an item to {@link Inventory} if {@code count > 0}, see {@link Inventory#remove_item}.",
+                " Docs are at <a href=\"https://example.com\">the site</a>.",
+                " <h2>Examples</h2>",
+                " <pre>{@code",
+                " inventory.add(1, 2);",
+                " }</pre>",
+                "",
+                " @param id the id of the item",
+                " @param count how many of them",
+                " @return The new total",
+                " @throws Exception If the inventory is full",
+            ]
+        );
+    }
+
+    #[test]
+    fn deprecated_and_hidden() {
+        let item: syn::ItemFn = syn::parse_str(
+            "/// Old method
+            #[deprecated(since = \"1.2\", note = \"use `bar`\")]
+            fn foo() {}",
+        )
+        .unwrap();
+        let docs = translate_docs(&item_docs(&item.attrs), Language::Java);
+        assert_eq!(
+            docs,
+            vec![
+                "# [doc = \" Old method\"]",
+                "# [doc = \"\"]",
+                "# [doc = \" @deprecated As of 1.2, use {@code bar}\"]"
+            ]
+        );
+        let item: syn::ItemFn = syn::parse_str(
+            "/// Internal
+            #[doc(hidden)]
+            #[deprecated]
+            fn foo() {}",
+        )
+        .unwrap();
+        assert!(is_doc_hidden(&item.attrs));
+        let docs = translate_docs(&item_docs(&item.attrs), Language::Cpp);
+        assert_eq!(docs, vec!["# [doc = \" \\\\deprecated\"]"]);
+    }
+
+    #[test]
+    fn doxygen() {
+        let doc = translate(DOC, Language::Cpp);
+        assert_eq!(
+            doc[0],
+            " Adds an item to Inventory if `count > 0`, see Inventory::remove_item."
+        );
+        assert!(doc.contains(&String::from(" \\code")));
+        assert!(doc.contains(&String::from(" \\param id the id of the item")));
+        assert!(doc.contains(&String::from(" \\return The new total")));
+        let hidden = vec![String::from("# [doc (hidden)]")];
+        assert_eq!(translate_docs(&hidden, Language::Java), hidden);
+    }
+}
@@ -1,4 +1,6 @@
+use crate::companion::CompanionFile;
 use crate::types_structs::{Enum, Struct, Trait};
+use crate::Language;
 
 ///Supported types
 #[derive(Debug)]
@@ -16,11 +18,27 @@ pub enum TypeHolder {
 }
 
 impl TypeHolder {
-    pub fn generate_interface(&mut self) -> String {
+    pub fn generate_interface(&mut self, language: Language) -> String {
         match self {
-            TypeHolder::Trait(ref mut val) => val.generate_interface(),
-            TypeHolder::Struct(ref mut val) => val.generate_interface(),
-            TypeHolder::Enum(ref mut val) => val.generate_interface(),
+            TypeHolder::Trait(ref mut val) => val.generate_interface(language),
+            TypeHolder::Struct(ref mut val) => val.generate_interface(language),
+            TypeHolder::Enum(ref mut val) => val.generate_interface(language),
+        }
+    }
+
+    pub fn translate_docs(&mut self, language: Language) {
+        match self {
+            TypeHolder::Trait(val) => val.translate_docs(language),
+            TypeHolder::Struct(val) => val.translate_docs(language),
+            TypeHolder::Enum(val) => val.translate_docs(language),
+        }
+    }
+
+    pub fn companion_code(&self, companion: &mut CompanionFile) {
+        match self {
+            TypeHolder::Trait(val) => val.companion_code(companion),
+            TypeHolder::Struct(val) => val.companion_code(companion),
+            TypeHolder::Enum(val) => val.companion_code(companion),
         }
     }
 
@@ -55,6 +73,69 @@ impl TypeHolder {
     }
 }
 
+///Smart pointer holding the instances of a class so that they can be shared ie between threads.\
+/// Set with `#[generate_interface_doc(shared = "arc_mutex")]` or `shared = "rc_refcell"`
+#[derive(Debug, Copy, Clone)]
+pub enum SharedOwnership {
+    ArcMutex,
+    RcRefCell,
+}
+
+impl SharedOwnership {
+    pub fn from_attribute(value: &str) -> Option<SharedOwnership> {
+        match value {
+            "arc_mutex" => Some(SharedOwnership::ArcMutex),
+            "rc_refcell" => Some(SharedOwnership::RcRefCell),
+            _ => None,
+        }
+    }
+
+    ///The type returned by the constructors in the interface file ie `Arc<Mutex<Foo>>`
+    pub fn wrap_type(&self, class_name: &str) -> String {
+        match self {
+            SharedOwnership::ArcMutex => format!("Arc<Mutex<{}>>", class_name),
+            SharedOwnership::RcRefCell => format!("Rc<RefCell<{}>>", class_name),
+        }
+    }
+
+    ///Same as [`SharedOwnership::wrap_type`] but doesn't need any import
+    pub fn full_type(&self, class_name: &str) -> String {
+        match self {
+            SharedOwnership::ArcMutex => {
+                format!("std::sync::Arc<std::sync::Mutex<{}>>", class_name)
+            }
+            SharedOwnership::RcRefCell => {
+                format!("std::rc::Rc<std::cell::RefCell<{}>>", class_name)
+            }
+        }
+    }
+
+    ///Expression wrapping `value` in the shared pointer
+    pub fn wrap_value(&self, value: &str) -> String {
+        match self {
+            SharedOwnership::ArcMutex => {
+                format!("std::sync::Arc::new(std::sync::Mutex::new({}))", value)
+            }
+            SharedOwnership::RcRefCell => {
+                format!("std::rc::Rc::new(std::cell::RefCell::new({}))", value)
+            }
+        }
+    }
+
+    ///Imports needed in the interface file by [`SharedOwnership::wrap_type`]
+    pub fn imports(&self) -> &'static str {
+        match self {
+            SharedOwnership::ArcMutex => "use std::sync::{Arc, Mutex};\n",
+            SharedOwnership::RcRefCell => "use std::cell::RefCell;\nuse std::rc::Rc;\n",
+        }
+    }
+
+    ///Checks if `return_type` (without whitespaces) is the class wrapped in this pointer
+    pub fn matches(&self, class_name: &str, return_type: &str) -> bool {
+        return_type == self.wrap_type(class_name) || return_type == self.full_type(class_name)
+    }
+}
+
 ///`Current` refers to just adding a new line\
 /// `ShiftRight` refers to adding a new line and then a tab more than the previous line\
 /// `ShiftLeft` refers to adding a new line and then a tab less than the previous line
@@ -69,3 +150,44 @@ pub enum Delimiters {
     Bracket,
     Parenthesis,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::SharedOwnership;
+
+    #[test]
+    fn shared_ownership() {
+        let arc_mutex = SharedOwnership::from_attribute("arc_mutex").unwrap();
+        assert!(matches!(arc_mutex, SharedOwnership::ArcMutex));
+        assert_eq!(arc_mutex.wrap_type("Foo"), "Arc<Mutex<Foo>>");
+        assert_eq!(
+            arc_mutex.full_type("Foo"),
+            "std::sync::Arc<std::sync::Mutex<Foo>>"
+        );
+        assert_eq!(
+            arc_mutex.wrap_value("Foo::new()"),
+            "std::sync::Arc::new(std::sync::Mutex::new(Foo::new()))"
+        );
+        assert!(arc_mutex.matches("Foo", "Arc<Mutex<Foo>>"));
+        assert!(arc_mutex.matches("Foo", "std::sync::Arc<std::sync::Mutex<Foo>>"));
+        assert!(!arc_mutex.matches("Foo", "Rc<RefCell<Foo>>"));
+        assert!(!arc_mutex.matches("Foo", "Arc<Mutex<Bar>>"));
+
+        let rc_refcell = SharedOwnership::from_attribute("rc_refcell").unwrap();
+        assert!(matches!(rc_refcell, SharedOwnership::RcRefCell));
+        assert_eq!(rc_refcell.wrap_type("Foo"), "Rc<RefCell<Foo>>");
+        assert_eq!(
+            rc_refcell.full_type("Foo"),
+            "std::rc::Rc<std::cell::RefCell<Foo>>"
+        );
+        assert_eq!(
+            rc_refcell.wrap_value("Foo::new()"),
+            "std::rc::Rc::new(std::cell::RefCell::new(Foo::new()))"
+        );
+        assert!(rc_refcell.matches("Foo", "Rc<RefCell<Foo>>"));
+        assert!(rc_refcell.matches("Foo", "std::rc::Rc<std::cell::RefCell<Foo>>"));
+        assert!(!rc_refcell.matches("Foo", "Arc<Mutex<Foo>>"));
+
+        assert!(SharedOwnership::from_attribute("arc").is_none());
+    }
+}
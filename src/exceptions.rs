@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use syn::__private::ToTokens;
+use syn::{GenericArgument, PathArguments, ReturnType, Signature, Type};
+
+///Java exceptions thrown by exported methods returning `Result<T, E>`.
+/// Set with `#[generate_interface(exception = "...")]` on the error types
+#[derive(Default)]
+pub struct Exceptions {
+    ///name of the error type to the JNI name of the exception class ie `com/example/FooException`
+    classes: HashMap<String, String>,
+    ///error types used by the exported methods, as they're written, and their names
+    used: Vec<(String, String)>,
+    ///methods whose error types have no exception
+    unmapped: Vec<(String, String)>,
+    ///methods returning an alias of `Result` ie `std::io::Result<T>` and the path of the alias
+    aliases: Vec<(String, String)>,
+}
+
+impl Exceptions {
+    pub fn add(&mut self, error_type: &str, exception_class: &str) {
+        self.classes
+            .insert(error_type.to_string(), exception_class.replace('.', "/"));
+    }
+
+    ///Records the error type of the method. `method` is ie `Foo::bar`.\
+    /// Error types are matched by the last segment of their path like the exported types so
+    /// `crate::errors::FooError` is `FooError`
+    pub fn add_method(&mut self, method: String, signature: &Signature) {
+        let (error_type, name) = match error_type(signature) {
+            Some(ErrorType::Type(val)) => (
+                val.to_token_stream().to_string().replace(' ', ""),
+                type_name(val),
+            ),
+            //the error type isn't written so it can't be matched with the exceptions
+            Some(ErrorType::Alias(alias)) => return self.aliases.push((method, alias)),
+            None => return,
+        };
+        //converted by flapigen
+        if error_type == "String" || error_type == "&str" {
+            return;
+        }
+        match name.filter(|it| self.classes.contains_key(it)) {
+            None => self.unmapped.push((method, error_type)),
+            //flapigen matches the typemaps with the types as they're written
+            Some(name) => {
+                if !self.used.iter().any(|(it, _)| *it == error_type) {
+                    self.used.push((error_type, name))
+                }
+            }
+        }
+    }
+
+    ///The typemaps of the error types used by the exported methods
+    pub fn typemaps(&mut self) -> String {
+        self.used.sort_unstable();
+        self.used
+            .iter()
+            .map(|(error_type, name)| typemap(error_type, &self.classes[name]))
+            .collect()
+    }
+
+    ///Reports the methods whose error types have no exception
+    pub fn report(&self) {
+        for (method, error_type) in &self.unmapped {
+            println!(
+                "cargo:warning={} returns Result<_, {}> but {} has no exception. Annotate it with \
+                #[generate_interface(exception = \"...\")] or add a typemap with foreign_typemap!",
+                method, error_type, error_type
+            )
+        }
+        for (method, alias) in &self.aliases {
+            println!(
+                "cargo:warning={} returns {}<_>, an alias of Result, so its error type isn't known. \
+                Add a typemap for it with foreign_typemap! or return Result<_, E> where E is \
+                annotated with #[generate_interface(exception = \"...\")]",
+                method, alias
+            )
+        }
+    }
+}
+
+///Error type of a method returning a `Result`
+enum ErrorType<'a> {
+    ///`E` of `Result<T, E>`
+    Type(&'a Type),
+    ///Path of an alias with only the type of the value ie `std::io::Result` for
+    /// `std::io::Result<T>` or `FooResult` for `FooResult<T>`
+    Alias(String),
+}
+
+///The error type of the method if it returns a `Result` or an alias of it
+fn error_type(signature: &Signature) -> Option<ErrorType<'_>> {
+    let path = match &signature.output {
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(val) if val.qself.is_none() => &val.path,
+            _ => return None,
+        },
+        ReturnType::Default => return None,
+    };
+    let segment = path.segments.last()?;
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().collect::<Vec<_>>(),
+        _ => return None,
+    };
+    match args[..] {
+        [_, GenericArgument::Type(error)] if segment.ident == "Result" => {
+            Some(ErrorType::Type(error))
+        }
+        [GenericArgument::Type(_)] if segment.ident.to_string().ends_with("Result") => {
+            let mut alias = path.clone();
+            alias.segments.last_mut().unwrap().arguments = PathArguments::None;
+            Some(ErrorType::Alias(
+                alias.to_token_stream().to_string().replace(' ', ""),
+            ))
+        }
+        _ => None,
+    }
+}
+
+///The last segment of the path of `ty` ie `FooError` for `crate::errors::FooError`
+fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(val) if val.qself.is_none() => {
+            val.path.segments.last().map(|it| it.ident.to_string())
+        }
+        _ => None,
+    }
+}
+
+///Same as flapigen's typemap for `Result<T, String>` but throwing the exception class
+fn typemap(error_type: &str, exception_class: &str) -> String {
+    let class_id = format!(
+        "RIFGEN_{}",
+        exception_class.replace(['/', '$'], "_").to_uppercase()
+    );
+    format!(
+        r#"foreign_typemap!(
+	($p:r_type) <T> Result<T, {error}> => swig_i_type!(T) {{
+		$out = match $p {{
+			Ok(x) => {{
+				swig_from_rust_to_i_type!(T, x, ret)
+				ret
+			}}
+			Err(err) => {{
+				let exception_class = swig_jni_find_class!({id}, "{class}");
+				jni_throw(env, exception_class, &err.to_string());
+				return <swig_i_type!(T)>::jni_invalid_value();
+			}}
+		}};
+	}};
+	($p:f_type, unique_prefix="/*Result<swig_subst_type!(T), {error}>*/") => "/*Result<swig_subst_type!(T), {error}>*/swig_f_type!(T)"
+		"swig_foreign_from_i_type!(T, $p)";
+);
+"#,
+        error = error_type,
+        id = class_id,
+        class = exception_class
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::exceptions::Exceptions;
+
+    #[test]
+    fn exceptions() {
+        let mut exceptions = Exceptions::default();
+        exceptions.add("NetworkError", "com.example.NetworkException");
+        let signature = |it: &str| syn::parse_str::<syn::Signature>(it).unwrap();
+        exceptions.add_method(
+            String::from("Conn::open"),
+            &signature("fn open(&self) -> Result<i32, NetworkError>"),
+        );
+        exceptions.add_method(
+            String::from("Conn::close"),
+            &signature("fn close(&self) -> Result<(), String>"),
+        );
+        exceptions.add_method(
+            String::from("Conn::read"),
+            &signature("fn read(&self) -> std::io::Result<Vec<u8>>"),
+        );
+        exceptions.add_method(
+            String::from("Conn::connect"),
+            &signature("fn connect(&self) -> Result<(), crate::errors::NetworkError>"),
+        );
+        exceptions.add_method(
+            String::from("Conn::write"),
+            &signature("fn write(&self) -> Result<(), std::io::Error>"),
+        );
+        exceptions.add_method(
+            String::from("Conn::flush"),
+            &signature("fn flush(&self) -> ConnResult<()>"),
+        );
+        exceptions.add_method(
+            String::from("Conn::peek"),
+            &signature("fn peek(&self) -> Option<u8>"),
+        );
+        assert_eq!(
+            exceptions.unmapped,
+            vec![(String::from("Conn::write"), String::from("std::io::Error"))]
+        );
+        //the error types of the aliases aren't known
+        assert_eq!(
+            exceptions.aliases,
+            vec![
+                (String::from("Conn::read"), String::from("std::io::Result")),
+                (String::from("Conn::flush"), String::from("ConnResult"))
+            ]
+        );
+        let typemaps = exceptions.typemaps();
+        assert!(typemaps.contains("Result<T, NetworkError> => swig_i_type!(T)"));
+        assert!(typemaps.contains("Result<T, crate::errors::NetworkError> => swig_i_type!(T)"));
+        assert!(typemaps.contains(
+            "swig_jni_find_class!(RIFGEN_COM_EXAMPLE_NETWORKEXCEPTION, \"com/example/NetworkException\")"
+        ));
+    }
+}
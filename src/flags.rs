@@ -0,0 +1,232 @@
+use inflector::Inflector;
+use syn::ItemEnum;
+
+///Suffix of the class holding a set of the flags of an enum ie `PermissionFlags`
+pub const FLAGS_CLASS_SUFFIX: &str = "Flags";
+
+///Set of the flags of an enum annotated with `#[generate_interface(flags)]` since the values of
+/// flapigen enums can't be combined. It's a struct of the companion file holding the bits
+pub struct FlagsClass {
+    pub name: String,
+    ///the Rust code of the struct, its consts and methods
+    pub definition: String,
+    ///the constructor creating the empty set
+    pub constructor: String,
+    ///`from_bits`, `bits`, `contains`, `insert` and `remove`
+    pub methods: Vec<String>,
+    ///the const holding each flag ie `READ_WRITE` for `ReadWrite`
+    pub consts: Vec<String>,
+}
+
+impl FlagsClass {
+    ///`bits_type` is the integer type of the values of the enum. `exported_type` implements
+    /// `rifgen::ExportedType` so the methods using the class pass the checks of `check-java`
+    /// and `check-cpp`
+    pub fn new(item: &ItemEnum, bits_type: &str, exported_type: bool) -> FlagsClass {
+        let enum_name = item.ident.to_string();
+        let name = format!("{}{}", enum_name, FLAGS_CLASS_SUFFIX);
+        let flags = item
+            .variants
+            .iter()
+            .map(|it| {
+                (
+                    it.ident.to_string().to_screaming_snake_case(),
+                    format!("{}::{} as {}", enum_name, it.ident, bits_type),
+                )
+            })
+            .collect::<Vec<_>>();
+        let constructor = format!(
+            "
+    ///The empty set
+    pub fn new() -> {} {{
+        {} {{ bits: 0 }}
+    }}
+",
+            name, name
+        );
+        let methods = vec![
+            format!(
+                "
+    ///Fails when `bits` has bits which don't belong to any flag
+    pub fn from_bits(bits: {bits}) -> Result<{name}, String> {{
+        if bits & !{name}::ALL_BITS != 0 {{
+            return Err(format!(\"Invalid bits for {name}: {{}}\", bits));
+        }}
+        Ok({name} {{ bits }})
+    }}
+",
+                bits = bits_type,
+                name = name
+            ),
+            format!(
+                "
+    ///The bits of the set flags
+    pub fn bits(&self) -> {} {{
+        self.bits
+    }}
+",
+                bits_type
+            ),
+            format!(
+                "
+    ///Whether all the flags of `other` are set
+    pub fn contains(&self, other: &{}) -> bool {{
+        self.bits & other.bits == other.bits
+    }}
+",
+                name
+            ),
+            format!(
+                "
+    ///Sets the flags of `other`
+    pub fn insert(&mut self, other: &{}) {{
+        self.bits |= other.bits
+    }}
+",
+                name
+            ),
+            format!(
+                "
+    ///Clears the flags of `other`
+    pub fn remove(&mut self, other: &{}) {{
+        self.bits &= !other.bits
+    }}
+",
+                name
+            ),
+        ];
+        let consts = flags
+            .iter()
+            .map(|(flag, value)| {
+                format!(
+                    "
+    pub const {}: {} = {} {{ bits: {} }};",
+                    flag, name, name, value
+                )
+            })
+            .collect::<String>();
+        let definition = format!(
+            "
+///Set of [`{enum_name}`] flags
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct {name} {{
+    bits: {bits},
+}}
+
+impl {name} {{{consts}
+    const ALL_BITS: {bits} = {all_bits};
+{constructor}{methods}}}
+
+impl From<{enum_name}> for {name} {{
+    fn from(flag: {enum_name}) -> {name} {{
+        {name} {{ bits: flag as {bits} }}
+    }}
+}}
+{exported_type}",
+            enum_name = enum_name,
+            name = name,
+            bits = bits_type,
+            consts = consts,
+            all_bits = flags
+                .iter()
+                .map(|(_, value)| value.as_str())
+                .collect::<Vec<_>>()
+                .join(" | "),
+            constructor = constructor,
+            methods = methods.concat(),
+            exported_type = if exported_type {
+                format!("\nimpl ::rifgen::ExportedType for {} {{}}\n", name)
+            } else {
+                String::new()
+            }
+        );
+        FlagsClass {
+            name,
+            definition,
+            constructor,
+            methods,
+            consts: flags.into_iter().map(|(flag, _)| flag).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::flags::FlagsClass;
+    use crate::{Generator, Language, TypeCases};
+
+    #[test]
+    fn flags_class() {
+        let item =
+            syn::parse_str("enum Permission { Read = 1, Write = 2, ReadWrite = 3 }").unwrap();
+        let flags = FlagsClass::new(&item, "u32", false);
+        assert_eq!(flags.name, "PermissionFlags");
+        assert_eq!(flags.consts, vec!["READ", "WRITE", "READ_WRITE"]);
+        assert!(flags.definition.contains(
+            "
+impl PermissionFlags {
+    pub const READ: PermissionFlags = PermissionFlags { bits: Permission::Read as u32 };
+    pub const WRITE: PermissionFlags = PermissionFlags { bits: Permission::Write as u32 };
+    pub const READ_WRITE: PermissionFlags = PermissionFlags { bits: Permission::ReadWrite as u32 };
+    const ALL_BITS: u32 = Permission::Read as u32 | Permission::Write as u32 | Permission::ReadWrite as u32;
+"
+        ));
+        assert_eq!(
+            flags.methods[2],
+            "
+    ///Whether all the flags of `other` are set
+    pub fn contains(&self, other: &PermissionFlags) -> bool {
+        self.bits & other.bits == other.bits
+    }
+"
+        );
+        assert!(flags
+            .definition
+            .contains("impl From<Permission> for PermissionFlags {"));
+        assert!(!flags.definition.contains("ExportedType"));
+        assert!(FlagsClass::new(&item, "u32", true)
+            .definition
+            .contains("\nimpl ::rifgen::ExportedType for PermissionFlags {}\n"));
+    }
+
+    #[test]
+    fn method_using_flags() {
+        let folder = std::env::temp_dir().join("rifgen_flags_test");
+        let source = folder.join("src");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(
+            source.join("lib.rs"),
+            "
+            #[generate_interface(flags)]
+            #[repr(u32)]
+            pub enum Permission { Read = 1, Write = 2 }
+
+            #[generate_interface_doc]
+            pub struct File;
+
+            impl File {
+                #[generate_interface(constructor)]
+                pub fn new() -> File { File }
+
+                #[generate_interface]
+                pub fn permissions(&self) -> PermissionFlags { PermissionFlags::READ }
+            }
+            ",
+        )
+        .unwrap();
+        Generator::new(TypeCases::CamelCase, Language::Java, vec![&source])
+            .generate_interface(folder.join("glue.in"));
+        let interface = std::fs::read_to_string(folder.join("glue.in")).unwrap();
+        let companion = std::fs::read_to_string(folder.join("glue_shims.rs")).unwrap();
+        std::fs::remove_dir_all(&folder).unwrap();
+        assert!(
+            interface.contains("fn File::permissions(& self)->PermissionFlags; alias permissions;")
+        );
+        assert!(interface.contains("class PermissionFlags {"));
+        //so the checks of the crate accept `permissions`
+        assert_eq!(
+            companion.contains("impl ::rifgen::ExportedType for PermissionFlags {}"),
+            cfg!(any(feature = "check-java", feature = "check-cpp"))
+        );
+    }
+}
@@ -1,12 +1,31 @@
-use crate::enums::{TypeHolder, Types};
+use crate::annotations::{item_annotations, ForeignAnnotation};
+use crate::async_fns::AsyncMethod;
+use crate::closures::ClosureMethod;
+use crate::companion::CompanionFile;
+use crate::consts::{constants_class, ConstGetter, DEFAULT_CONSTANTS_CLASS};
+use crate::docs::{is_doc_hidden, item_docs};
+use crate::enums::{SharedOwnership, TypeHolder, Types};
+use crate::exceptions::Exceptions;
+use crate::flags::FlagsClass;
+use crate::implemented_traits::ImplementedTraits;
+use crate::tagged_enums::TaggedEnum;
 use crate::types_structs::{Enum, ItemInfo, Struct, Trait, TYPE_CASE};
-use crate::{Language, TypeCases};
+use crate::visibility::VisibilityCheck;
+use crate::{DocHiddenPolicy, Language, TypeCases, VisibilityPolicy};
 use derive_new::new;
-use rifgen_attributes_utils::generate_impl_block;
+use rifgen_attributes_utils::callbacks::{
+    callback_shape_errors, callback_supertraits, skip_reason,
+};
+use rifgen_attributes_utils::enum_values::enum_values;
+use rifgen_attributes_utils::ffi_types::FfiLanguage;
+use rifgen_attributes_utils::{
+    find_attribute_args, generate_builder, generate_impl_block, has_flag, string_arg, string_args,
+    AccessOptions,
+};
 use std::collections::{HashMap, VecDeque};
 use std::fs::{DirEntry, File};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::time::Instant;
 use syn::__private::ToTokens;
@@ -21,77 +40,46 @@ pub const F_ENUM: &str = "foreign_enum!";
 //errors
 const UNABLE_TO_READ: &str = "Unable to read file: ";
 
+///Settings from [`crate::Generator`] which aren't specific to a type
+#[derive(Default)]
+pub struct Options {
+    pub object_methods: bool,
+    pub companion_file: Option<PathBuf>,
+    pub visibility: VisibilityPolicy,
+    pub glue_module: Option<String>,
+    pub raw_docs: bool,
+    pub doc_hidden: DocHiddenPolicy,
+    pub async_executor: Option<String>,
+    pub constants_class: Option<String>,
+}
+
 //helper macros
 #[derive(new, Debug)]
 struct AttrCheck {
     is_attribute: bool,
-    is_constructor: bool,
 }
 
 fn has_gen_access_methods_attr(item: &ItemStruct) -> bool {
     item.attrs
         .iter()
-        .map(|attr| attr.path.segments.iter())
-        .flatten()
+        .flat_map(|attr| attr.path.segments.iter())
         .any(|seg| {
-            eprintln!("token {} container {}",seg.to_token_stream()
-                .to_string(),seg.to_token_stream()
-                .to_string()
-                .contains("generate_access_methods"));
             seg.to_token_stream()
                 .to_string()
                 .contains("generate_access_methods")
         })
 }
 
-// TODO: make it more accurate
-fn has_clone_derive_struct(item: &ItemStruct) -> bool {
-    item.attrs.iter().any(|attr| {
-        let attr: String = attr.to_token_stream().to_string();
-        attr.starts_with("# [deriv") && {
-            //could be derive, derivable, derivative, etc
-            attr.find("Clone")
-                .and_then(|index| {
-                    Some((
-                        attr.get(index.saturating_sub(1)..index),
-                        attr.get(index + 5..index + 6),
-                    ))
-                })
-                .map(|(before, after)| {
-                    before
-                        .map(|e| !e.chars().next().unwrap().is_alphanumeric())
-                        .unwrap_or_default()
-                        && after
-                            .map(|e| !e.chars().next().unwrap().is_alphanumeric())
-                            .unwrap_or_default()
-                })
-                .unwrap_or_default()
-        }
-    })
-}
-
 macro_rules! has_gen_attr {
-    ($expr:expr) => {
-        has_gen_attr!($expr, false)
-    };
-    ($expr:expr,$check_for_constructor:expr) => {{
-        let mut is_attribute = false;
-        let mut is_constructor = false;
-        $expr.attrs.iter().any(|it| {
-            is_attribute = it
-                .path
-                .segments
-                .iter()
-                .any(|it| {
-                eprintln!("hsas atter {}",it.to_token_stream().to_string());
-                it.to_token_stream().to_string().contains("generate_interface")
-                });
-            if is_attribute && $check_for_constructor {
-                is_constructor = it.tokens.to_string().contains("constructor");
-            }
-            is_attribute
+    ($expr:expr) => {{
+        let is_attribute = $expr.attrs.iter().any(|it| {
+            it.path.segments.iter().any(|it| {
+                it.to_token_stream()
+                    .to_string()
+                    .contains("generate_interface")
+            })
         });
-        AttrCheck::new(is_attribute, is_constructor)
+        AttrCheck::new(is_attribute)
     }};
 }
 
@@ -99,70 +87,29 @@ macro_rules! has_gen_attr {
 macro_rules! has_doc_gen_attr {
     ($expr:expr) => {
         $expr.attrs.iter().any(|it| {
-            it.path
-                .segments
-                .iter()
-                .any(|it| it.to_token_stream().to_string().contains("generate_interface_doc"))
+            it.path.segments.iter().any(|it| {
+                it.to_token_stream()
+                    .to_string()
+                    .contains("generate_interface_doc")
+            })
         })
     };
 }
 macro_rules! get_doc {
     ($expr:expr) => {
-        $expr
-            .attrs
-            .iter()
-            .filter_map(|it| {
-                //segments usually contain an item but just in case
-                let doc = it
-                    .path
-                    .segments
-                    .iter()
-                    .map(|it| it.ident.to_string())
-                    .filter(|it| it == "doc")
-                    .next();
-                //
-                if let Some(_) = doc {
-                    Some(it.to_token_stream().to_string())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<String>>()
+        item_docs(&$expr.attrs)
     };
 }
 
-macro_rules! correct_string {
-    ($string:expr) => {{
-        let sign = $string.rfind("<");
-        if let Some(sign) = sign {
-            //means it's something like Box<Kofi>
-            // find the other >
-            let other = $string.find(">").unwrap();
-            let new_string = &$string[sign + 1..other];
-            new_string.trim().to_string()
-        } else {
-            $string.trim().into()
-        }
-    }};
-}
-
 macro_rules! types_in_method {
     ($expr:expr) => {{
-        let result = ($expr)
-            .sig
-            .inputs
-            .iter()
-            .filter_map(|it| {
-                match it {
-                    syn::FnArg::Receiver(_) => None, //something like (&self)
-                    syn::FnArg::Typed(typ) => {
-                        let _type = typ.ty.to_token_stream().to_string().replace(" dyn ", "");
-                        ////println!("type {}", _type);
-                        Some(correct_string!(_type))
-                    }
-                }
-            })
-            .collect::<Vec<_>>();
+        let mut result = Vec::new();
+        ($expr).sig.inputs.iter().for_each(|it| {
+            match it {
+                syn::FnArg::Receiver(_) => {} //something like (&self)
+                syn::FnArg::Typed(typ) => type_names(&typ.ty, &mut result),
+            }
+        });
         result
     }};
 }
@@ -170,31 +117,43 @@ macro_rules! types_in_method {
 macro_rules! return_types {
     ($expr:expr) => {{
         let mut return_types: Vec<String> = Vec::new();
-        match &$expr.sig.output {
-            ReturnType::Type(_, val) => match &**val {
-                Type::Path(val) => {
-                    //let result = vec![];
-                    val.path.segments.iter().for_each(|it| {
-                        return_types.push((&it.ident).to_string());
-                        match &it.arguments {
-                            PathArguments::AngleBracketed(val) => {
-                                val.args.iter().for_each(|it| {
-                                    let str = it.to_token_stream().to_string();
-                                    return_types.push(str);
-                                });
-                            }
-                            _ => {}
-                        }
-                    });
-                }
-                _ => {}
-            },
-            _ => {}
+        if let ReturnType::Type(_, val) = &$expr.sig.output {
+            type_names(val, &mut return_types)
         }
         return_types
     }};
 }
 
+///Names of all the types used in `ty` so the exported types it depends on can be placed
+/// before it ie `HashMap`, `String` and `Foo` for `&HashMap<String, Foo>`
+fn type_names(ty: &Type, out: &mut Vec<String>) {
+    match ty {
+        Type::Path(val) => val.path.segments.iter().for_each(|it| {
+            out.push(it.ident.to_string());
+            if let PathArguments::AngleBracketed(val) = &it.arguments {
+                val.args.iter().for_each(|it| {
+                    if let syn::GenericArgument::Type(ty) = it {
+                        type_names(ty, out)
+                    }
+                })
+            }
+        }),
+        Type::Reference(val) => type_names(&val.elem, out),
+        Type::Slice(val) => type_names(&val.elem, out),
+        Type::Array(val) => type_names(&val.elem, out),
+        Type::Paren(val) => type_names(&val.elem, out),
+        Type::Tuple(val) => val.elems.iter().for_each(|it| type_names(it, out)),
+        Type::TraitObject(val) => val.bounds.iter().for_each(|it| {
+            if let syn::TypeParamBound::Trait(bound) = it {
+                if let Some(segment) = bound.path.segments.last() {
+                    out.push(segment.ident.to_string())
+                }
+            }
+        }),
+        _ => {}
+    }
+}
+
 macro_rules! function_signature {
     ($expr:expr) => {{
         {
@@ -336,18 +295,63 @@ impl ItemsHolder {
         self.enums_list.push(data)
     }
 
-    fn generate_interface<P: AsRef<Path>>(mut self, language: Language, out_file: P) {
+    fn generate_interface<P: AsRef<Path>>(
+        mut self,
+        language: Language,
+        out_file: P,
+        companion_file: Option<&Path>,
+        mut exceptions: Exceptions,
+    ) {
         //println!("final {:?}", self.final_list);
+        let mut companion = CompanionFile::default();
+        let default_companion_file = companion_file_path(out_file.as_ref());
         let mut file = File::create(out_file).expect("Unable to write to disk");
         file.write_all(b"//Automatically generated by rifgen\nuse crate::*;\n")
             .unwrap();
         if matches!(language, Language::Java) {
             file.write_all(b"use jni_sys::*;\n").unwrap();
+            //read by `rifgen::foreign_annotations`
+            let mut annotations = self
+                .list
+                .values()
+                .filter_map(|it| match it {
+                    TypeHolder::Struct(val) => Some(val),
+                    _ => None,
+                })
+                .flat_map(|it| {
+                    it.annotations
+                        .iter()
+                        .chain(it.extras.iter().flat_map(|it| it.annotations.iter()))
+                })
+                .collect::<Vec<_>>();
+            annotations.sort_unstable();
+            annotations.dedup();
+            for annotation in annotations {
+                file.write_all(ForeignAnnotation::new(annotation).comment().as_bytes())
+                    .unwrap();
+            }
+            exceptions.report();
+            file.write_all(exceptions.typemaps().as_bytes()).unwrap();
+        }
+        //imports for the return type of the constructors of shared classes
+        let mut imports = self
+            .list
+            .values()
+            .filter_map(|it| match it {
+                TypeHolder::Struct(val) => val.shared.map(|it| it.imports()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        imports.sort_unstable();
+        imports.dedup();
+        for import in imports {
+            file.write_all(import.as_bytes()).unwrap();
         }
         //first add enums since enums "can't" depend on other data structures
         self.sort_items();
         for mut enums in self.enums_list {
-            file.write_all(enums.generate_interface().as_ref())
+            enums.companion_code(&mut companion);
+            file.write_all(enums.generate_interface(language).as_ref())
                 .expect("Unable to write to disk");
         }
 
@@ -363,18 +367,333 @@ impl ItemsHolder {
         );*/
         //println!("tested");
         for name in self.final_list {
-            file.write_all(
-                self.list
-                    .get_mut(&*name)
-                    .unwrap()
-                    .generate_interface()
-                    .as_ref(),
-            )
-            .expect("Unable to write to disk");
+            let item = self.list.get_mut(&*name).unwrap();
+            item.companion_code(&mut companion);
+            file.write_all(item.generate_interface(language).as_ref())
+                .expect("Unable to write to disk");
+        }
+        //the user may include the companion file so always write it when the path was given
+        match companion_file {
+            Some(path) => companion.write(path),
+            None if !companion.is_empty() => companion.write(default_companion_file),
+            None => {}
+        }
+    }
+}
+
+///`glue.in` would have `glue_shims.rs` in the same folder
+fn companion_file_path(interface_file_path: &Path) -> PathBuf {
+    let stem = interface_file_path
+        .file_stem()
+        .and_then(|it| it.to_str())
+        .unwrap_or("interface");
+    interface_file_path.with_file_name(format!("{}_shims.rs", stem))
+}
+
+///Records the derives of a struct or enum, warning about types with the same name since their
+/// traits get merged
+fn record_derives(
+    implemented_traits: &mut ImplementedTraits,
+    ident: &syn::Ident,
+    attrs: &[syn::Attribute],
+) {
+    if !implemented_traits.add_derives(&ident.to_string(), attrs) {
+        println!(
+            "cargo:warning=Found several types named {}. Their derives and trait impls are \
+            merged since types are matched by name",
+            ident
+        )
+    }
+}
+
+///Removes the methods taking or returning `#[doc(hidden)]` types which were skipped
+fn skip_hidden_uses(type_name: &str, extras: &mut Vec<ItemInfo>, hidden_types: &[String]) {
+    extras.retain(|extra| {
+        let method = match &extra.method_info {
+            Some(method) => method,
+            None => return true,
+        };
+        match method.all_types().find(|it| hidden_types.contains(it)) {
+            Some(hidden) => {
+                println!(
+                    "cargo:warning=Skipped {}::{} since it uses {}, which is #[doc(hidden)]",
+                    type_name,
+                    method.name(),
+                    hidden
+                );
+                false
+            }
+            None => true,
         }
+    })
+}
+
+///Warns about the methods found in impl blocks read before the enum, which are dropped
+/// since only the methods of structs are exported
+fn skip_enum_methods(enum_name: &str, methods: &[ItemInfo]) {
+    for method in methods.iter().filter_map(|it| it.method_info.as_ref()) {
+        println!(
+            "cargo:warning=Skipped {}::{} since only the methods of structs are exported",
+            enum_name,
+            method.name()
+        );
     }
 }
 
+///Records the exception of an error type with `#[generate_interface(exception = "...")]`.
+/// Returns whether it has one
+fn add_exception(
+    exceptions: &mut Exceptions,
+    ident: &syn::Ident,
+    attrs: &[syn::Attribute],
+) -> bool {
+    //invalid attributes are reported by the macro when the crate is compiled
+    let exception = find_attribute_args(attrs, "generate_interface")
+        .and_then(|args| string_arg(&args, "exception"));
+    match exception {
+        Some(exception) => {
+            exceptions.add(&ident.to_string(), &exception);
+            true
+        }
+        None => false,
+    }
+}
+
+///Replaces the async methods of `class` with the wrappers taking completion callbacks.
+/// Returns the callbacks
+fn async_callbacks(class: &mut Struct, executor: Option<&str>) -> Vec<Trait> {
+    let mut callbacks = vec![];
+    for extra in std::mem::take(&mut class.extras) {
+        let signature = match &extra.method_info {
+            Some(method_info) if method_info.rust_signature().asyncness.is_some() => {
+                method_info.rust_signature()
+            }
+            _ => {
+                class.extras.push(extra);
+                continue;
+            }
+        };
+        let method_name = signature.ident.to_string();
+        let async_method = match (executor, extra.is_constructor) {
+            (_, true) => Err(String::from("constructors can't be async")),
+            (None, _) => Err(String::from(
+                "async methods need an executor. Set it with Generator::async_executor",
+            )),
+            (Some(executor), _) => AsyncMethod::new(
+                &class.name,
+                signature,
+                class.traits.contains("Clone"),
+                executor,
+            ),
+        };
+        let async_method = match async_method {
+            Ok(val) => val,
+            Err(reason) => {
+                println!(
+                    "cargo:warning=Skipped {}::{} since {}",
+                    class.name, method_name, reason
+                );
+                continue;
+            }
+        };
+        let wrapper = syn::parse_str::<syn::ImplItemMethod>(&async_method.wrapper)
+            .expect("Invalid async wrapper");
+        let mut item_info = ItemInfo::new_method(
+            function_signature!(wrapper),
+            extra.docs,
+            method_name,
+            false,
+            types_in_method!(wrapper),
+            return_types!(wrapper),
+            wrapper.sig.clone(),
+        );
+        item_info.annotations = extra.annotations;
+        class.extras.push(item_info);
+        callbacks.push(generated_callback(async_method.callback));
+        class.wrappers.push(async_method.wrapper);
+    }
+    callbacks
+}
+
+///Replaces the methods of `class` taking closures with the wrappers taking callbacks.
+/// Returns the callbacks
+fn closure_callbacks(class: &mut Struct) -> Vec<Trait> {
+    let mut callbacks = vec![];
+    for extra in std::mem::take(&mut class.extras) {
+        let closure_method = match &extra.method_info {
+            Some(method_info) => ClosureMethod::new(&class.name, method_info.rust_signature()),
+            None => None,
+        };
+        let closure_method = match (closure_method, class.shared) {
+            (None, _) => {
+                class.extras.push(extra);
+                continue;
+            }
+            (Some(_), Some(_)) if extra.is_constructor => Err(String::from(
+                "constructors of shared classes can't take closures",
+            )),
+            (Some(val), _) => val,
+        };
+        let method_name = extra.method_info.as_ref().unwrap().name().to_string();
+        let closure_method = match closure_method {
+            Ok(val) => val,
+            Err(reason) => {
+                println!(
+                    "cargo:warning=Skipped {}::{} since {}",
+                    class.name, method_name, reason
+                );
+                continue;
+            }
+        };
+        let wrapper = syn::parse_str::<syn::ImplItemMethod>(&closure_method.wrapper)
+            .expect("Invalid closure wrapper");
+        let mut item_info = ItemInfo::new_method(
+            function_signature!(wrapper),
+            extra.docs,
+            method_name,
+            extra.is_constructor,
+            types_in_method!(wrapper),
+            return_types!(wrapper),
+            wrapper.sig.clone(),
+        );
+        item_info.annotations = extra.annotations;
+        class.extras.push(item_info);
+        callbacks.extend(closure_method.callbacks.into_iter().map(generated_callback));
+        class.wrappers.push(closure_method.wrapper);
+    }
+    callbacks
+}
+
+///The method of a class calling `shim`, a method of the companion file, named `name`
+fn shim_item_info(shim: &str, name: String, docs: Vec<String>) -> ItemInfo {
+    let shim = syn::parse_str::<syn::ImplItemMethod>(shim).expect("Invalid shim");
+    ItemInfo::new_method(
+        function_signature!(shim),
+        docs,
+        name,
+        false,
+        types_in_method!(shim),
+        return_types!(shim),
+        shim.sig.clone(),
+    )
+}
+
+///The class holding a set of the flags of an enum annotated with `#[generate_interface(flags)]`
+fn flags_class(
+    item: &syn::ItemEnum,
+    bits_type: &str,
+    implemented_traits: &mut ImplementedTraits,
+) -> Struct {
+    //the methods using the class are checked like the ones using exported types
+    let exported_type = cfg!(any(feature = "check-java", feature = "check-cpp"));
+    let flags = FlagsClass::new(item, bits_type, exported_type);
+    let definition = syn::parse_str::<syn::File>(&flags.definition).expect("Invalid flags class");
+    if let Some(syn::Item::Struct(val)) = definition.items.first() {
+        record_derives(implemented_traits, &val.ident, &val.attrs)
+    }
+    let mut class = Struct::new(
+        flags.name.to_string(),
+        Types::Struct,
+        get_doc!(item),
+        vec![],
+    );
+    let constructor = syn::parse_str::<syn::ImplItemMethod>(&flags.constructor)
+        .expect("Invalid flags constructor");
+    let mut item_info = shim_item_info(
+        &flags.constructor,
+        constructor.sig.ident.to_string(),
+        get_doc!(constructor),
+    );
+    item_info.is_constructor = true;
+    class.extras.push(item_info);
+    for method in &flags.methods {
+        let parsed = syn::parse_str::<syn::ImplItemMethod>(method).expect("Invalid flags method");
+        class.extras.push(shim_item_info(
+            method,
+            parsed.sig.ident.to_string(),
+            get_doc!(parsed),
+        ));
+    }
+    //the flags are static getters like other consts
+    let self_type = syn::parse_str::<Type>(&flags.name).expect("Invalid flags class");
+    for (flag, variant) in flags.consts.iter().zip(item.variants.iter()) {
+        let ident = syn::Ident::new(flag, variant.ident.span());
+        let getter = ConstGetter::new(Some(&flags.name), &ident, &self_type);
+        class.extras.push(shim_item_info(
+            &getter.shim,
+            getter.name.to_string(),
+            get_doc!(variant),
+        ));
+        class.wrappers.push(getter.shim);
+    }
+    class.object_methods = true;
+    class.definition = Some(flags.definition);
+    class
+}
+
+///The class of an enum annotated with `#[generate_interface(tagged)]` and the enum of its variants
+fn tagged_enum(item: &syn::ItemEnum) -> (Struct, Enum) {
+    let name = item.ident.to_string();
+    let tagged = TaggedEnum::new(item);
+    let variants = item
+        .variants
+        .iter()
+        .map(|it| ItemInfo::new_enum(it.ident.to_string(), get_doc!(it)))
+        .collect();
+    let mut kind = Enum::new(
+        tagged.kind_name,
+        Types::Enum,
+        vec![format!("# [doc = \" Variants of [`{}`]\"]", name)],
+        variants,
+    );
+    kind.definition = Some(tagged.kind_enum);
+    let mut class = Struct::new(name, Types::Struct, get_doc!(item), vec![]);
+    for method in tagged.methods {
+        class
+            .extras
+            .push(shim_item_info(&method.shim, method.name, method.docs));
+        class.wrappers.push(method.shim);
+    }
+    //flapigen requires a constructor with the self_type so the first factory is also one
+    class.private_constructor = class.extras.first().map(|it| it.signature.to_string());
+    (class, kind)
+}
+
+///The `foreign_callback!` of a callback generated by rifgen. `definition` is its Rust code
+fn generated_callback(definition: String) -> Trait {
+    let callback = syn::parse_str::<syn::File>(&definition).expect("Invalid generated callback");
+    let callback = callback
+        .items
+        .iter()
+        .find_map(|it| match it {
+            syn::Item::Trait(val) => Some(val),
+            _ => None,
+        })
+        .expect("Invalid generated callback");
+    //`Box<dyn Callback>` is already `Send` due to the supertrait
+    let mut trait_data = Trait::new(
+        callback.ident.to_string(),
+        Types::Trait,
+        get_doc!(callback),
+        vec![],
+    );
+    for item in &callback.items {
+        if let syn::TraitItem::Method(method) = item {
+            trait_data.extras.push(ItemInfo::new_method(
+                function_signature!(method),
+                get_doc!(method),
+                method.sig.ident.to_string(),
+                false,
+                types_in_method!(method),
+                return_types!(method),
+                method.sig.clone(),
+            ));
+        }
+    }
+    trait_data.definition = Some(definition);
+    trait_data
+}
+
 // one possible implementation of walking a directory only visiting files
 fn visit_dirs<P: AsRef<Path>>(
     dir: P,
@@ -397,6 +716,7 @@ fn visit_dirs<P: AsRef<Path>>(
 pub struct FileGenerator<I: AsRef<Path>, S: AsRef<Path>> {
     interface_file_path: I,
     source_folders: Vec<S>,
+    options: Options,
 }
 
 impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
@@ -404,11 +724,13 @@ impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
         type_case: TypeCases,
         interface_file_path: I,
         source_folders: Vec<S>,
+        options: Options,
     ) -> FileGenerator<I, S> {
         unsafe { TYPE_CASE = type_case }
         FileGenerator {
             interface_file_path,
             source_folders,
+            options,
         }
     }
 
@@ -416,47 +738,96 @@ impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
         let start = Instant::now();
         //the closure to be applied to each file
         let mut file_data: HashMap<Rc<String>, TypeHolder> = HashMap::new();
-        let mut closure = |file: &DirEntry| {
+        //derives and trait impls of every struct and enum, whether exported or not
+        let mut implemented_traits = ImplementedTraits::default();
+        let mut visibility =
+            VisibilityCheck::new(self.options.visibility, self.options.glue_module.as_deref());
+        //`#[doc(hidden)]` structs, enums and callbacks
+        let mut hidden_types = Vec::new();
+        let mut exceptions = Exceptions::default();
+        //getters of the consts which aren't associated with a type
+        let mut constants = vec![];
+        //getters of the associated consts, added once all the types are known
+        let mut impl_consts = vec![];
+        let constants_class_name = self
+            .options
+            .constants_class
+            .as_deref()
+            .unwrap_or(DEFAULT_CONSTANTS_CLASS);
+        let ffi_language = match language {
+            Language::Java => FfiLanguage::Java,
+            Language::Cpp => FfiLanguage::Cpp,
+        };
+        let mut closure = |folder: &Path, file: &DirEntry| {
             let file_path = file.path();
+            visibility.set_file(folder, &file_path);
             let file_contents = std::fs::read_to_string(&file_path)
                 .unwrap_or_else(|_| panic!("{}{}", UNABLE_TO_READ, file_path.to_str().unwrap()));
             let compiled_file = syn::parse_file(&file_contents)
-                .expect(&format!("Invalid Rust file at {:?}", file_path.to_str()));
+                .unwrap_or_else(|_| panic!("Invalid Rust file at {:?}", file_path.to_str()));
             for item in &compiled_file.items {
                 //
                 match item {
                     syn::Item::Struct(item) => {
+                        record_derives(&mut implemented_traits, &item.ident, &item.attrs);
+                        add_exception(&mut exceptions, &item.ident, &item.attrs);
                         //check if it has the doc attribute
                         if has_doc_gen_attr!(item) || has_gen_access_methods_attr(item) {
-                            let name = Rc::new(item.ident.to_string());
-                            //assert!(!file_data.contains_key(&name.clone()));
-                            //the impl block may come (ie if it's in a different file) before the struct definition
-                            if let Some(val) = file_data.get_mut(&name) {
-                                match val {
-                                    TypeHolder::Struct(val) => {
-                                        val.docs.append(&mut get_doc!(item));
-                                    }
-                                    _ => {
-                                        panic!("Expected {} to be a struct", name)
-                                    }
-                                }
-                            } else {
-                                file_data.insert(
-                                    name.clone(),
-                                    TypeHolder::Struct(Struct::new(
-                                        name.to_string(),
-                                        Types::Struct,
-                                        get_doc!(item),
-                                        vec![],
-                                        has_clone_derive_struct(item),
-                                    )),
-                                );
+                            FileGenerator::<&Path, &Path>::struct_data(&mut file_data, item);
+                            if is_doc_hidden(&item.attrs) {
+                                hidden_types.push(item.ident.to_string())
                             }
                         }
                         if has_gen_access_methods_attr(item) {
-                            let impl_block = generate_impl_block(item);
-                            FileGenerator::<&Path, &Path>::impl_data(&mut file_data, &impl_block);
+                            //invalid attributes are reported by the macro when the crate is compiled
+                            let options = AccessOptions::from_args(
+                                &find_attribute_args(&item.attrs, "generate_access_methods")
+                                    .unwrap_or_default(),
+                            );
+                            let impl_block = options
+                                .as_ref()
+                                .ok()
+                                .and_then(|it| generate_impl_block(item, it).ok());
+                            if let (Ok(options), Some(impl_block)) = (options, impl_block) {
+                                FileGenerator::<&Path, &Path>::impl_data(
+                                    &mut file_data,
+                                    &impl_block,
+                                    &mut visibility,
+                                    self.options.doc_hidden,
+                                    &mut impl_consts,
+                                );
+                                if let Some((builder, builder_impl)) = options
+                                    .builder
+                                    .then(|| generate_builder(item).ok())
+                                    .flatten()
+                                {
+                                    FileGenerator::<&Path, &Path>::struct_data(
+                                        &mut file_data,
+                                        &builder,
+                                    );
+                                    FileGenerator::<&Path, &Path>::impl_data(
+                                        &mut file_data,
+                                        &builder_impl,
+                                        &mut visibility,
+                                        self.options.doc_hidden,
+                                        &mut impl_consts,
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    syn::Item::Const(val) if has_gen_attr!(val).is_attribute => {
+                        if (self.options.doc_hidden == DocHiddenPolicy::Skip
+                            && is_doc_hidden(&val.attrs))
+                            || !visibility.check_item(constants_class_name, &val.vis, &val.ident)
+                        {
+                            continue;
                         }
+                        let getter = ConstGetter::new(None, &val.ident, &val.ty);
+                        constants.push((
+                            shim_item_info(&getter.shim, getter.name.to_string(), get_doc!(val)),
+                            getter.shim,
+                        ));
                     }
                     syn::Item::Fn(val) => {
                         // function not in impl block
@@ -469,50 +840,158 @@ impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
                         }
                     }
                     syn::Item::Impl(val) => {
+                        implemented_traits.add_impl(val);
                         //TODO let it work with enums
-                        FileGenerator::<&Path, &Path>::impl_data(&mut file_data, val);
+                        FileGenerator::<&Path, &Path>::impl_data(
+                            &mut file_data,
+                            val,
+                            &mut visibility,
+                            self.options.doc_hidden,
+                            &mut impl_consts,
+                        );
                     }
-                    syn::Item::Enum(val) => {
-                        if has_gen_attr!(val).is_attribute {
-                            let name = Rc::new(val.ident.to_string());
+                    syn::Item::Enum(val) if has_gen_attr!(val).is_attribute => {
+                        record_derives(&mut implemented_traits, &val.ident, &val.attrs);
+                        //error types are thrown as exceptions instead
+                        if add_exception(&mut exceptions, &val.ident, &val.attrs) {
+                            continue;
+                        }
+                        if is_doc_hidden(&val.attrs) {
+                            hidden_types.push(val.ident.to_string())
+                        }
+                        let name = Rc::new(val.ident.to_string());
+                        //impl blocks read before the enum added their methods to a struct
+                        if let Some(TypeHolder::Struct(methods)) = file_data.get(&name) {
+                            skip_enum_methods(&name, &methods.extras);
+                            file_data.remove(&name);
+                        }
+                        assert!(
+                            !file_data.contains_key(&name),
+                            "Multiple definitions of {}",
+                            &name
+                        ); // make sure no other struct has the same name
+                        let tagged = find_attribute_args(&val.attrs, "generate_interface")
+                            .is_some_and(|args| has_flag(&args, "tagged"));
+                        if tagged {
+                            let (class, kind) = tagged_enum(val);
+                            let kind_name = Rc::new(kind.name.to_string());
                             assert!(
-                                !file_data.contains_key(&name),
+                                !file_data.contains_key(&kind_name),
                                 "Multiple definitions of {}",
-                                &name
-                            ); // make sure no other struct has the same name
-                            let variants = val
-                                .variants
-                                .iter()
-                                .map(|it| ItemInfo::new_enum(it.ident.to_string(), get_doc!(it)))
-                                .collect();
-                            file_data.insert(
-                                name.clone(),
-                                TypeHolder::Enum(Enum::new(
-                                    name.to_string(),
-                                    Types::Enum,
-                                    get_doc!(val),
-                                    variants,
-                                    false,
-                                )),
+                                &kind_name
                             );
+                            if is_doc_hidden(&val.attrs) {
+                                hidden_types.push(kind_name.to_string())
+                            }
+                            file_data.insert(name, TypeHolder::Struct(class));
+                            file_data.insert(kind_name, TypeHolder::Enum(kind));
+                            continue;
+                        }
+                        //the macro reports it with a span
+                        if let Some(variant) = val
+                            .variants
+                            .iter()
+                            .find(|it| !matches!(it.fields, syn::Fields::Unit))
+                        {
+                            println!(
+                                "cargo:warning=Skipped enum {} since {}::{} has fields, which \
+                                flapigen enums can't hold. Export it as a class with \
+                                #[generate_interface(tagged)]",
+                                name, name, variant.ident
+                            );
+                            continue;
+                        }
+                        let values = match enum_values(val, ffi_language) {
+                            Some(Ok(values)) => Some(values),
+                            Some(Err(error)) => panic!("Values of {}: {}", name, error),
+                            None => None,
+                        };
+                        //exported as the set of flags instead
+                        if find_attribute_args(&val.attrs, "generate_interface")
+                            .is_some_and(|args| has_flag(&args, "flags"))
+                        {
+                            let bits_type = values.as_ref().map_or("i32", |it| it.value_type);
+                            let class = flags_class(val, bits_type, &mut implemented_traits);
+                            let class_name = Rc::new(class.name.to_string());
+                            assert!(
+                                !file_data.contains_key(&class_name),
+                                "Multiple definitions of {}",
+                                &class_name
+                            );
+                            if is_doc_hidden(&val.attrs) {
+                                hidden_types.push(class_name.to_string())
+                            }
+                            file_data.insert(class_name, TypeHolder::Struct(class));
+                            continue;
                         }
+                        let variants = val
+                            .variants
+                            .iter()
+                            .enumerate()
+                            .map(|(index, it)| {
+                                let mut variant =
+                                    ItemInfo::new_enum(it.ident.to_string(), get_doc!(it));
+                                variant.discriminant =
+                                    values.as_ref().and_then(|values| values.values[index]);
+                                variant
+                            })
+                            .collect();
+                        let mut item =
+                            Enum::new(name.to_string(), Types::Enum, get_doc!(val), variants);
+                        item.value_type = values.map(|it| it.value_type.to_string());
+                        file_data.insert(name.clone(), TypeHolder::Enum(item));
+                    }
+                    syn::Item::Enum(val) => {
+                        record_derives(&mut implemented_traits, &val.ident, &val.attrs)
                     }
                     syn::Item::Trait(val) => {
                         if !has_gen_attr!(val).is_attribute {
                             continue;
                         }
                         //println!("trait");
+                        //the macro reports these with spans
+                        let (supertraits, mut errors) = callback_supertraits(val);
+                        errors.append(&mut callback_shape_errors(val));
+                        if !errors.is_empty() {
+                            errors.iter().for_each(|error| {
+                                println!("cargo:warning=Skipped callback {}: {}", val.ident, error)
+                            });
+                            continue;
+                        }
+                        if is_doc_hidden(&val.attrs) {
+                            hidden_types.push(val.ident.to_string())
+                        }
                         let name = Rc::new(val.ident.to_string());
                         let mut trait_data: Trait = Trait::new(
                             name.to_string(),
                             Types::Trait,
                             get_doc!(val),
                             Vec::with_capacity(val.items.len()),
-                            false,
                         );
+                        trait_data.supertraits = supertraits;
                         for item in &val.items {
                             if let syn::TraitItem::Method(method) = item {
                                 let method_name = method.sig.ident.to_string();
+                                if let Some(reason) = skip_reason(method) {
+                                    if reason.is_automatic() {
+                                        println!(
+                                            "cargo:warning=Skipped {}::{} since {}",
+                                            name,
+                                            method_name,
+                                            reason.description()
+                                        )
+                                    }
+                                    continue;
+                                }
+                                if self.options.doc_hidden == DocHiddenPolicy::Skip
+                                    && is_doc_hidden(&method.attrs)
+                                {
+                                    println!(
+                                        "cargo:warning=Skipped {}::{} since it's #[doc(hidden)]",
+                                        name, method_name
+                                    );
+                                    continue;
+                                }
                                 trait_data.extras.push(ItemInfo::new_method(
                                     function_signature!(method),
                                     get_doc!(method),
@@ -520,6 +999,7 @@ impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
                                     false,
                                     types_in_method!(method),
                                     return_types!(method),
+                                    method.sig.clone(),
                                 ));
                             }
                         }
@@ -537,12 +1017,112 @@ impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
             }
         };
         for folder in self.source_folders.iter() {
-            visit_dirs(folder, &mut closure).expect("Unable to read directory");
+            visit_dirs(folder, &mut |file| closure(folder.as_ref(), file))
+                .expect("Unable to read directory");
+        }
+        visibility.finish();
+        for (name, const_name, item_info, shim) in impl_consts {
+            let data = file_data.entry(Rc::new(name.clone())).or_insert_with(|| {
+                TypeHolder::Struct(Struct::new(name.clone(), Types::Struct, vec![], vec![]))
+            });
+            match data {
+                TypeHolder::Struct(val) => {
+                    val.extras.push(item_info);
+                    val.wrappers.push(shim);
+                }
+                _ => println!(
+                    "cargo:warning=Skipped {}::{} since only the consts of structs are exported",
+                    name, const_name
+                ),
+            }
+        }
+        if self.options.doc_hidden == DocHiddenPolicy::Skip {
+            for name in &hidden_types {
+                println!("cargo:warning=Skipped {} since it's #[doc(hidden)]", name);
+                file_data.remove(name);
+            }
+            //the methods of the other types can't use them either
+            for (name, type_holder) in file_data.iter_mut() {
+                match type_holder {
+                    TypeHolder::Struct(val) => {
+                        skip_hidden_uses(name, &mut val.extras, &hidden_types)
+                    }
+                    TypeHolder::Trait(val) => {
+                        skip_hidden_uses(name, &mut val.extras, &hidden_types)
+                    }
+                    TypeHolder::Enum(_) => {}
+                }
+            }
+        }
+        if !constants.is_empty() {
+            let data = file_data
+                .entry(Rc::new(constants_class_name.to_string()))
+                .or_insert_with(|| {
+                    let mut data = Struct::new(
+                        constants_class_name.to_string(),
+                        Types::Struct,
+                        vec![],
+                        vec![],
+                    );
+                    data.definition = Some(constants_class(constants_class_name));
+                    TypeHolder::Struct(data)
+                });
+            match data {
+                TypeHolder::Struct(val) => {
+                    for (item_info, shim) in constants {
+                        val.extras.push(item_info);
+                        val.wrappers.push(shim);
+                    }
+                }
+                _ => panic!(
+                    "{} holds the exported consts so it should be a struct",
+                    constants_class_name
+                ),
+            }
+        }
+        //completion callbacks of the async methods
+        let mut callbacks = vec![];
+        for (name, type_holder) in file_data.iter_mut() {
+            match type_holder {
+                TypeHolder::Struct(val) => {
+                    val.traits = implemented_traits.take(name);
+                    val.object_methods |= self.options.object_methods;
+                    callbacks.append(&mut async_callbacks(
+                        val,
+                        self.options.async_executor.as_deref(),
+                    ));
+                    callbacks.append(&mut closure_callbacks(val));
+                    if matches!(language, Language::Java) {
+                        for method in val.extras.iter().filter_map(|it| it.method_info.as_ref()) {
+                            exceptions.add_method(
+                                format!("{}::{}", name, method.name()),
+                                method.rust_signature(),
+                            )
+                        }
+                    }
+                }
+                TypeHolder::Enum(val) => val.traits = implemented_traits.take(name),
+                TypeHolder::Trait(_) => {}
+            }
+        }
+        for callback in callbacks {
+            let name = Rc::new(callback.name.to_string());
+            match file_data.get(&name) {
+                //closures with the same signature share the callback
+                Some(TypeHolder::Trait(val)) if val.definition == callback.definition => {}
+                Some(_) => panic!("Multiple definitions of {}", &name),
+                None => {
+                    file_data.insert(name, TypeHolder::Trait(callback));
+                }
+            }
         }
         //create interface file
         let mut holder = ItemsHolder::new(file_data.len());
         //file_data.iter().for_each(|it| println!("it {:?}", it));
-        for (name, type_holder) in file_data {
+        for (name, mut type_holder) in file_data {
+            if !self.options.raw_docs {
+                type_holder.translate_docs(language)
+            }
             match type_holder {
                 TypeHolder::Struct(_) | TypeHolder::Trait(_) => {
                     holder.add_items(name, type_holder);
@@ -550,11 +1130,55 @@ impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
                 TypeHolder::Enum(val) => holder.add_enum(val),
             }
         }
-        holder.generate_interface(language, &self.interface_file_path);
+        holder.generate_interface(
+            language,
+            &self.interface_file_path,
+            self.options.companion_file.as_deref(),
+            exceptions,
+        );
         println!("Total Time Taken To Generate File {:?}", start.elapsed());
     }
 
-    fn impl_data(map: &mut HashMap<Rc<String>, TypeHolder>, item: &syn::ItemImpl) {
+    ///Adds the struct, with its doc comment and the options of `#[generate_interface_doc]`
+    fn struct_data(map: &mut HashMap<Rc<String>, TypeHolder>, item: &ItemStruct) {
+        let name = Rc::new(item.ident.to_string());
+        let doc_args =
+            find_attribute_args(&item.attrs, "generate_interface_doc").unwrap_or_default();
+        let object_methods = has_flag(&doc_args, "object_methods");
+        //unsupported values are reported by the macro when the crate is compiled
+        let shared =
+            string_arg(&doc_args, "shared").and_then(|it| SharedOwnership::from_attribute(&it));
+        let annotations = item_annotations(&item.attrs, string_args(&doc_args, "annotate"));
+        //assert!(!map.contains_key(&name.clone()));
+        //the impl block may come (ie if it's in a different file) before the struct definition
+        if let Some(val) = map.get_mut(&name) {
+            match val {
+                TypeHolder::Struct(val) => {
+                    val.docs.append(&mut get_doc!(item));
+                    val.object_methods = object_methods;
+                    val.shared = shared;
+                    val.annotations = annotations;
+                }
+                _ => {
+                    panic!("Expected {} to be a struct", name)
+                }
+            }
+        } else {
+            let mut data = Struct::new(name.to_string(), Types::Struct, get_doc!(item), vec![]);
+            data.object_methods = object_methods;
+            data.shared = shared;
+            data.annotations = annotations;
+            map.insert(name.clone(), TypeHolder::Struct(data));
+        }
+    }
+
+    fn impl_data(
+        map: &mut HashMap<Rc<String>, TypeHolder>,
+        item: &syn::ItemImpl,
+        visibility: &mut VisibilityCheck,
+        doc_hidden: DocHiddenPolicy,
+        consts: &mut Vec<(String, String, ItemInfo, String)>,
+    ) {
         let self_type = &*item.self_ty;
         if let syn::Type::Path(type_path) = self_type {
             let name = type_path
@@ -563,45 +1187,88 @@ impl<I: AsRef<Path>, S: AsRef<Path>> FileGenerator<I, S> {
                 .iter()
                 .next()
                 .map(|it| it.ident.to_string());
+            //#[generate_interface] on the impl block exports all the public methods
+            let export_all = item.trait_.is_none()
+                && find_attribute_args(&item.attrs, "generate_interface").is_some();
             if let Some(name) = name {
                 //name of struct or enum
                 for item in item.items.iter() {
+                    if let syn::ImplItem::Const(item) = item {
+                        let is_exported = find_attribute_args(&item.attrs, "generate_interface")
+                            .map(|args| !has_flag(&args, "skip"))
+                            .unwrap_or_default();
+                        if !is_exported
+                            || (doc_hidden == DocHiddenPolicy::Skip && is_doc_hidden(&item.attrs))
+                            || !visibility.check_item(&name, &item.vis, &item.ident)
+                        {
+                            continue;
+                        }
+                        let getter = ConstGetter::new(Some(&name), &item.ident, &item.ty);
+                        let item_info =
+                            shim_item_info(&getter.shim, getter.name.to_string(), get_doc!(item));
+                        //the type may be defined in a file which wasn't read yet
+                        consts.push((name.clone(), item.ident.to_string(), item_info, getter.shim));
+                    }
                     if let syn::ImplItem::Method(method) = item {
-                        let method_info: AttrCheck = has_gen_attr!(method, true);
+                        let args = find_attribute_args(&method.attrs, "generate_interface");
+                        let is_exported = match &args {
+                            Some(args) => !has_flag(args, "skip"),
+                            None => export_all && matches!(method.vis, syn::Visibility::Public(_)),
+                        };
+                        if !is_exported {
+                            continue;
+                        }
+                        if doc_hidden == DocHiddenPolicy::Skip && is_doc_hidden(&method.attrs) {
+                            println!(
+                                "cargo:warning=Skipped {}::{} since it's #[doc(hidden)]",
+                                name, method.sig.ident
+                            );
+                            continue;
+                        }
                         //not supporting enums for now
-                        if !method_info.is_attribute {
+                        if !visibility.check(&name, method) {
                             continue;
                         }
+                        let is_constructor = args
+                            .as_ref()
+                            .map(|args| has_flag(args, "constructor"))
+                            .unwrap_or_default();
                         let method_name = method.sig.ident.to_string();
                         let data = map.get_mut(&name);
-                        let item_info = ItemInfo::new_method(
+                        let mut item_info = ItemInfo::new_method(
                             function_signature!(method),
                             get_doc!(method),
                             method_name,
-                            method_info.is_constructor,
+                            is_constructor,
                             types_in_method!(method),
                             return_types!(method),
+                            method.sig.clone(),
+                        );
+                        item_info.annotations = item_annotations(
+                            &method.attrs,
+                            args.map(|args| string_args(&args, "annotate"))
+                                .unwrap_or_default(),
                         );
                         if let Some(data) = data {
                             match data {
                                 TypeHolder::Struct(val) => {
                                     val.extras.push(item_info);
                                 }
-                                _ => {
-                                    unimplemented!("Impl function may only be used for structs")
-                                }
+                                _ => println!(
+                                    "cargo:warning=Skipped {}::{} since only the methods of structs are exported",
+                                    name, method.sig.ident
+                                ),
                             }
                         } else {
                             //impl block came before struct definition (properly due to the order in which the files
                             // were read)
-                            //we're assuming the impl method is for a struct
-                            //if it's for an enum, it would crash in the enum function
+                            //we're assuming the impl method is for a struct.
+                            //If it's for an enum, the methods are skipped once the enum is read
                             let data = Struct::new(
                                 name.to_string(),
                                 Types::Struct,
                                 vec![],
                                 vec![item_info],
-                                false,
                             );
                             map.insert(Rc::new(name.clone()), TypeHolder::Struct(data));
                         }
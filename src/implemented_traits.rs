@@ -0,0 +1,142 @@
+use std::collections::{HashMap, HashSet};
+use syn::{Attribute, ItemImpl, Meta, NestedMeta, Type};
+
+///Traits derived or manually implemented by each type.\
+/// Since impl blocks may live in a different file from the type definition, the traits are
+/// gathered while walking through all the files and then handed to the exported types at the end
+#[derive(Debug, Default)]
+pub struct ImplementedTraits {
+    traits: HashMap<String, HashSet<String>>,
+    ///the types whose derives were recorded
+    definitions: HashSet<String>,
+}
+
+impl ImplementedTraits {
+    ///Records the traits in `#[derive(..)]` and `#[cfg_attr(.., derive(..))]` attributes.\
+    /// Types are matched by name like the exported classes, so it returns false when a type with
+    /// the same name was already recorded since the traits of both are merged
+    pub fn add_derives(&mut self, type_name: &str, attrs: &[Attribute]) -> bool {
+        let derives = derived_traits(attrs);
+        if !derives.is_empty() {
+            self.traits
+                .entry(type_name.to_string())
+                .or_default()
+                .extend(derives);
+        }
+        self.definitions.insert(type_name.to_string())
+    }
+
+    ///Records the trait of a trait impl block ie `impl Clone for Foo`
+    pub fn add_impl(&mut self, item: &ItemImpl) {
+        if let Some((type_name, trait_name)) = implemented_trait(item) {
+            self.traits.entry(type_name).or_default().insert(trait_name);
+        }
+    }
+
+    ///Removes and returns the traits recorded for `type_name`
+    pub fn take(&mut self, type_name: &str) -> HashSet<String> {
+        self.traits.remove(type_name).unwrap_or_default()
+    }
+}
+
+///Name of the traits derived by the item. Only the last segment of each path is kept
+/// so `std::clone::Clone` and `Clone` are the same
+pub fn derived_traits(attrs: &[Attribute]) -> Vec<String> {
+    let mut result = Vec::new();
+    attrs
+        .iter()
+        .filter_map(|attr| attr.parse_meta().ok())
+        .for_each(|meta| derives_in_meta(&meta, &mut result));
+    result
+}
+
+fn derives_in_meta(meta: &Meta, out: &mut Vec<String>) {
+    let list = match meta {
+        Meta::List(list) => list,
+        _ => return,
+    };
+    if list.path.is_ident("derive") {
+        list.nested.iter().for_each(|it| {
+            if let NestedMeta::Meta(Meta::Path(path)) = it {
+                if let Some(segment) = path.segments.last() {
+                    out.push(segment.ident.to_string())
+                }
+            }
+        })
+    } else if list.path.is_ident("cfg_attr") {
+        //the first item is the predicate, the rest are the attributes
+        list.nested.iter().skip(1).for_each(|it| {
+            if let NestedMeta::Meta(meta) = it {
+                derives_in_meta(meta, out)
+            }
+        })
+    }
+}
+
+///Returns the name of the type and the trait for `impl Trait for Type` blocks
+fn implemented_trait(item: &ItemImpl) -> Option<(String, String)> {
+    let (negative, trait_path, _) = item.trait_.as_ref()?;
+    if negative.is_some() {
+        //impl !Send for Foo
+        return None;
+    }
+    let type_name = match &*item.self_ty {
+        Type::Path(type_path) => type_path.path.segments.last()?.ident.to_string(),
+        _ => return None,
+    };
+    let trait_name = trait_path.segments.last()?.ident.to_string();
+    Some((type_name, trait_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::implemented_traits::{derived_traits, ImplementedTraits};
+    use syn::{Item, ItemStruct};
+
+    #[test]
+    fn derives() {
+        let item: ItemStruct = syn::parse_str(
+            "#[derive(Debug, std::clone::Clone)]
+            #[cfg_attr(feature = \"serde\", derive(Serialize), derive(PartialEq))]
+            #[derivative(Hash)]
+            struct Foo { data: i32 }",
+        )
+        .unwrap();
+        assert_eq!(
+            derived_traits(&item.attrs),
+            vec!["Debug", "Clone", "Serialize", "PartialEq"]
+        );
+        let item: ItemStruct =
+            syn::parse_str("#[derive(CloneFrom)] #[doc = \"Clone\"] struct Foo;").unwrap();
+        assert_eq!(derived_traits(&item.attrs), vec!["CloneFrom"]);
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut traits = ImplementedTraits::default();
+        let item: ItemStruct = syn::parse_str("#[derive(Clone)] struct Foo;").unwrap();
+        assert!(traits.add_derives("Foo", &item.attrs));
+        assert!(traits.add_derives("Bar", &[]));
+        assert!(!traits.add_derives("Foo", &[]));
+        assert!(!traits.add_derives("Bar", &item.attrs));
+    }
+
+    #[test]
+    fn impls() {
+        let mut traits = ImplementedTraits::default();
+        for item in [
+            "impl Clone for Foo { fn clone(&self) -> Self { todo!() } }",
+            "impl<'a> std::fmt::Display for crate::Foo { }",
+            "impl !Send for Foo {}",
+            "impl Foo { fn clone(&self) -> Self { todo!() } }",
+        ] {
+            if let Item::Impl(item) = syn::parse_str(item).unwrap() {
+                traits.add_impl(&item)
+            }
+        }
+        let foo = traits.take("Foo");
+        assert_eq!(foo.len(), 2);
+        assert!(foo.contains("Clone") && foo.contains("Display"));
+        assert!(traits.take("Foo").is_empty());
+    }
+}
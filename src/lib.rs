@@ -57,7 +57,7 @@
 //! use rifgen::{Generator, TypeCases, Language};
 //! let source_folder = "/user/projects"; //use your projects folder
 //! let out_file = "/user/projects/glue.in";
-//! Generator::new(TypeCases::CamelCase,Language::Java,source_folder)
+//! Generator::new(TypeCases::CamelCase,Language::Java,vec![source_folder])
 //! .generate_interface(out_file)
 //! ```
 //!
@@ -87,7 +87,43 @@
 //! }
 //! ```
 //!
-//! This crate works with doc comments so all doc comments would be preserved
+//! This crate works with doc comments so all doc comments would be preserved.
+//! Rustdoc conventions such as the `# Arguments` section and intra-doc links are converted to
+//! Javadoc or Doxygen. See [`Generator::raw_docs`]
+//!
+//! `#[deprecated]` is added to the doc comment as `@deprecated` (or `\deprecated` for Doxygen).
+//! Deprecated structs and methods are also annotated with `@Deprecated` in Java through the
+//! callbacks of [`ForeignAnnotation`]. flapigen doesn't call them for C++ so C++ classes only get
+//! the Doxygen tag.
+//! The doc comments of `#[doc(hidden)]` items are left out. See [`Generator::doc_hidden`]
+//!
+//! Methods returning `Result<T, E>` throw a Java exception when `E` is annotated with
+//! `#[generate_interface(exception = "com.example.FooException")]`. The typemaps are added to
+//! the interface file and the message of the exception is `E`'s `Display` output. The exception
+//! class should be written in Java, extending `Exception` with a constructor taking the message.
+//! Error types without an exception are reported when the interface file is generated, and so
+//! are aliases like `std::io::Result<T>` since their error type isn't written
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! #[generate_interface(exception = "com.example.NetworkException")]
+//! #[derive(Debug)]
+//! enum NetworkError {
+//!     Timeout,
+//!     Refused(String),
+//! }
+//! # impl std::fmt::Display for NetworkError {
+//! #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+//! #         write!(f, "{:?}", self)
+//! #     }
+//! # }
+//! ```
+//!
+//! Java annotations are added with `#[generate_interface(annotate = "@Keep")]` on methods and
+//! `#[generate_interface_doc(annotate = "@Keep")]` on structs. See [`ForeignAnnotation`] for
+//! the flapigen callbacks adding them.
+//! `@Nullable` and `@NonNull` are inferred by flapigen from `Option<T>` and other object types
+//! once `JavaConfig::use_null_annotation_from_package` is set.
+//!
 //! Use `#[generate_interface_doc]` on <b>structs only</b> to preserve the doc comment of the struct
 //! ```
 //! ///Data holder
@@ -98,6 +134,86 @@
 //! }
 //! ```
 //!
+//! `#[generate_interface_doc(object_methods)]` also exports `equals`, `hashCode` and `toString`
+//! when the struct implements `PartialEq`, `Hash` and `Display` (or `Debug`). In Java `PartialEq`
+//! is exported as `contentEquals`, while `equals(Object)` and `hashCode` are only overridden when
+//! the struct implements both `PartialEq` and `Hash`, to keep their contract.
+//! Helper methods needed for this are written to a companion Rust file. See [`Generator::companion_file`]
+//! ```
+//! # use rifgen_attr::generate_interface_doc;
+//! #[generate_interface_doc(object_methods)]
+//! #[derive(PartialEq, Hash, Debug)]
+//! struct Point {
+//!     x: i32
+//! }
+//! ```
+//!
+//! `async fn` methods are exported as methods taking a completion callback instead, ie
+//! `ConnFetchCallback` for `Conn::fetch` with `on_success` and `on_error`. The future is spawned
+//! on the executor set with [`Generator::async_executor`]. Methods taking `&self` need the struct
+//! to implement `Clone` since the future outlives the call, and so do the types of the other
+//! reference arguments except `&str` and slices. The errors of methods returning `Result` are
+//! passed to `on_error` as their `Display` output
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! #[derive(Clone)]
+//! struct Conn;
+//!
+//! impl Conn {
+//!     #[generate_interface]
+//!     async fn fetch(&self, url: &str) -> Result<String, std::io::Error> {
+//!         Ok(url.to_string())
+//!     }
+//! }
+//! ```
+//!
+//! Closure arguments (`impl Fn(i32) -> bool`, `Box<dyn Fn(String)>` and `&dyn FnMut(i32)`) are
+//! replaced with callbacks generated for each closure signature ie `I32ToBoolFn` with a `call`
+//! method. The foreign callback is turned back into the closure by the companion file.
+//! Closures can't be `Sync` since the callbacks aren't
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! struct Numbers {
+//!     items: Vec<i32>,
+//! }
+//!
+//! impl Numbers {
+//!     #[generate_interface]
+//!     fn count(&self, predicate: impl Fn(i32) -> bool) -> i32 {
+//!         self.items.iter().filter(|it| predicate(**it)).count() as i32
+//!     }
+//! }
+//! ```
+//!
+//! Annotated consts are exported as static getters since flapigen can't export fields, ie
+//! `getMaxSize` for `MAX_SIZE`. Associated consts are added to the class of their struct, those
+//! of enums and callbacks are skipped with a warning, and the others are added to the class set
+//! with [`Generator::constants_class`]
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! ///Version of the library
+//! #[generate_interface]
+//! pub const VERSION: &str = "1.0";
+//!
+//! struct Buffer;
+//!
+//! impl Buffer {
+//!     ///Largest size of a buffer
+//!     #[generate_interface]
+//!     pub const MAX_SIZE: usize = 1024;
+//! }
+//! ```
+//!
+//! To share the instances of a struct, ie between threads, use `shared = "arc_mutex"` (or `"rc_refcell"`).
+//! Constructors returning the struct itself are then wrapped in `Arc<Mutex<T>>`
+//! ```
+//! # use rifgen_attr::generate_interface_doc;
+//! #[generate_interface_doc(shared = "arc_mutex")]
+//! struct Counter {
+//!     count: i32
+//! }
+//! ```
+//!
 //! For `trait` just annotate the trait definition
 //! ```
 //! ///MyCallback documentation
@@ -118,16 +234,83 @@
 //!     Two
 //! }
 //! ```
+//! The ordinal of a flapigen enum isn't its discriminant. Enums with discriminants or an
+//! integer `#[repr]` get a `StatusValues` class with `value` and `fromValue` converting the
+//! variants to their values and back. The values should fit the `#[repr]` type, or `int`
+//! without one
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! #[generate_interface]
+//! #[repr(u16)]
+//! enum Status {
+//!     Ok = 200,
+//!     NotFound = 404,
+//! }
+//! ```
+//!
+//! flapigen enums can't hold data so enums with fields are exported as a class with
+//! `#[generate_interface(tagged)]`. Each variant gets a static factory ie `Shape.rect(w, h)`,
+//! `kind()` returns the variant as the `ShapeKind` enum and each field a getter ie `getRectW`
+//! throwing on the other variants. The fields are returned as clones
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! #[generate_interface(tagged)]
+//! enum Shape {
+//!     Circle(f64),
+//!     Rect { w: f64, h: f64 },
+//! }
+//! ```
+//!
+//! The values of flapigen enums can't be combined so enums of flags annotated with
+//! `#[generate_interface(flags)]` are exported as a set of flags instead, ie the
+//! `PermissionFlags` class with `contains`, `insert`, `remove`, `bits`, `fromBits` and
+//! `getRead` for each flag. It's a struct of the companion file holding the bits
+//! ```
+//! # use rifgen_attr::generate_interface;
+//! #[generate_interface(flags)]
+//! #[repr(u32)]
+//! enum Permission {
+//!     Read = 1,
+//!     Write = 2,
+//! }
+//! ```
+//!
+//! Enable the `check-java` or `check-cpp` feature to check the argument and return types of the
+//! annotated methods when the crate is compiled rather than when flapigen runs.
+//! Types flapigen can't convert without a typemap are reported as errors.
+//! Other types should be exported structs, enums or callbacks which implement [`ExportedType`].
+//! Use `#[generate_interface(unchecked)]` on methods using custom typemaps.
+//! Classes defined by the companion file, like the set of flags, implement it when the
+//! build dependency has the feature too
+//! ```toml
+//! [dependencies]
+//! rifgen = { version = "*", features = ["check-java"] }
+//!
+//! [build-dependencies]
+//! rifgen = { version = "*", features = ["check-java"] }
+//! ```
+mod annotations;
+mod async_fns;
+mod closures;
+mod companion;
+mod consts;
+mod docs;
 mod enums;
+mod exceptions;
+mod flags;
 mod generator_lib;
+mod implemented_traits;
 mod maps;
+mod tagged_enums;
 mod text_formatter;
 mod traits;
 mod types_structs;
+mod visibility;
 
 pub extern crate rifgen_attr;
 
-use crate::generator_lib::FileGenerator;
+pub use crate::annotations::{foreign_annotations, ForeignAnnotation};
+use crate::generator_lib::{FileGenerator, Options};
 use std::path::Path;
 
 /// The various type cases to use when generating interface files
@@ -148,14 +331,71 @@ pub struct Generator<P: AsRef<Path>> {
     type_case: TypeCases,
     scr_folder: Vec<P>,
     language: Language,
+    options: Options,
 }
 
 ///Supported languages for now
+#[derive(Copy, Clone)]
 pub enum Language {
     Java,
     Cpp,
 }
 
+///What to do with exported methods which can't be called from the module the interface file is
+/// included in, ie private methods of a struct in another module
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum VisibilityPolicy {
+    /// Export them and print a warning for each of them.
+    /// This is the default setting
+    #[default]
+    Warn,
+    /// Fail with the list of the methods
+    Error,
+    /// Leave them out of the interface file
+    Skip,
+}
+
+///What to do with exported items marked with `#[doc(hidden)]`
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum DocHiddenPolicy {
+    /// Export them without their doc comments.
+    /// This is the default setting
+    #[default]
+    DropDocs,
+    /// Leave the methods, structs, enums and callbacks out of the interface file.
+    /// Enum variants are always exported since flapigen needs all of them
+    Skip,
+}
+
+///Implemented for exported types so the `check-java` and `check-cpp` features can check
+/// that the types used by methods are exported.\
+/// `#[generate_interface_doc]` implements it for structs and `#[generate_interface]` for enums
+/// and callbacks (as `dyn Trait`).
+/// Implement it manually for types exported some other way
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` isn't exported by rifgen",
+    note = "annotate it with #[generate_interface_doc], or add a typemap for it with \
+    foreign_typemap! and skip the check with #[generate_interface(unchecked)]"
+)]
+pub trait ExportedType {}
+
+#[doc(hidden)]
+pub fn assert_exported<T: ?Sized + ExportedType>() {}
+
+///Implemented for error types mapped to Java exceptions with
+/// `#[generate_interface(exception = "...")]` so `check-java` can check the errors of the
+/// `Result`s returned by methods. The message of the exception is the `Display` output of the error
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` isn't mapped to a Java exception",
+    note = "annotate it with #[generate_interface(exception = \"com.example.FooException\")], \
+    or add a typemap for the Result with foreign_typemap! and skip the check with \
+    #[generate_interface(unchecked)]"
+)]
+pub trait ExceptionType: std::fmt::Display {}
+
+#[doc(hidden)]
+pub fn assert_exception<T: ExceptionType>() {}
+
 impl<S: AsRef<Path>> Generator<S> {
     /// Creates a new generator instance
     ///
@@ -166,14 +406,95 @@ impl<S: AsRef<Path>> Generator<S> {
             type_case,
             scr_folder,
             language,
+            options: Options::default(),
         }
     }
 
+    /// Export `equals`, `hashCode` and `toString` for every struct implementing `PartialEq`,
+    /// `Hash` and `Display` (or `Debug`).
+    ///
+    /// Without this, only structs annotated with `#[generate_interface_doc(object_methods)]` get them
+    pub fn object_methods(mut self, enabled: bool) -> Generator<S> {
+        self.options.object_methods = enabled;
+        self
+    }
+
+    /// `companion_file_path` refers to the path of the Rust file holding helper methods needed by
+    /// the interface file (ie `hashCode`). Include it in the same module as the interface file.
+    ///
+    /// By default it's placed next to the interface file and only written when needed,
+    /// ie `glue.in` would have `glue_shims.rs`
+    pub fn companion_file<C: AsRef<Path>>(mut self, companion_file_path: C) -> Generator<S> {
+        self.options.companion_file = Some(companion_file_path.as_ref().to_path_buf());
+        self
+    }
+
+    /// What to do with exported methods the interface file can't call. See [`Generator::glue_module`]
+    pub fn non_public_methods(mut self, policy: VisibilityPolicy) -> Generator<S> {
+        self.options.visibility = policy;
+        self
+    }
+
+    /// The module the interface file is included in ie `crate::java_glue`. Defaults to `crate`.
+    ///
+    /// The modules of the methods are found from the path of their files in the source folders
+    /// so `a/b.rs` is in `crate::a::b`
+    pub fn glue_module<M: Into<String>>(mut self, module: M) -> Generator<S> {
+        self.options.glue_module = Some(module.into());
+        self
+    }
+
+    /// What to do with exported items marked with `#[doc(hidden)]`
+    pub fn doc_hidden(mut self, policy: DocHiddenPolicy) -> Generator<S> {
+        self.options.doc_hidden = policy;
+        self
+    }
+
+    /// Path of the function spawning the futures of the exported `async fn` methods ie
+    /// `crate::runtime::spawn`. It's called with an `impl Future<Output = ()> + 'static` which
+    /// is also `Send` when the future of the method is.
+    /// The errors of methods returning `Result<T, E>` are passed to the `on_error` of the callback
+    /// with `E::to_string` so `E` should implement `Display`.
+    ///
+    /// Without it, async methods are skipped
+    /// ```
+    /// # use std::future::Future;
+    /// pub fn spawn<F: Future<Output = ()> + Send + 'static>(future: F) {
+    ///     # /*
+    ///     tokio::spawn(future);
+    ///     # */
+    /// }
+    /// ```
+    pub fn async_executor<E: Into<String>>(mut self, executor: E) -> Generator<S> {
+        self.options.async_executor = Some(executor.into());
+        self
+    }
+
+    /// Name of the class holding the exported consts which aren't associated with a type.
+    /// Defaults to `Constants`. It can be an exported struct
+    pub fn constants_class<C: Into<String>>(mut self, class_name: C) -> Generator<S> {
+        self.options.constants_class = Some(class_name.into());
+        self
+    }
+
+    /// Keep the doc comments as they are. By default, rustdoc conventions are converted to
+    /// Javadoc or Doxygen ie `# Arguments` to `@param`, ``[`Foo`]`` to `{@link Foo}` and code
+    /// blocks to `<pre>{@code ...}</pre>`
+    pub fn raw_docs(mut self, enabled: bool) -> Generator<S> {
+        self.options.raw_docs = enabled;
+        self
+    }
+
     ///`interface_file_path` refers to the path of the output file.
     /// If it exists, it would be overwritten
     pub fn generate_interface<I: AsRef<Path>>(self, interface_file_path: I) {
-        FileGenerator::new(self.type_case, interface_file_path, self.scr_folder)
-            .build(self.language);
+        FileGenerator::new(
+            self.type_case,
+            interface_file_path,
+            self.scr_folder,
+            self.options,
+        )
+        .build(self.language);
     }
 }
 
@@ -188,4 +509,4 @@ mod tests {
                 .generate_interface(format!("C:\\Users\\taimoor\\IdeaProjects\\rifgen\\src\\TEST{}.in", i))
         }
     }
-}*/
\ No newline at end of file
+}*/
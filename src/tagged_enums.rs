@@ -0,0 +1,223 @@
+use crate::docs::item_docs;
+use inflector::Inflector;
+use syn::__private::ToTokens;
+use syn::{Fields, ItemEnum};
+
+pub const TAGGED_ENUM_PREFIX: &str = "rifgen_tagged_";
+///Suffix of the enum of the variants of a tagged enum ie `ShapeKind`
+pub const KIND_ENUM_SUFFIX: &str = "Kind";
+
+///An enum with fields exported as a class since flapigen enums can't hold data.
+/// `kind` returns the variant as a plain enum, each variant has a static factory
+/// and each field a getter which fails on the other variants
+pub struct TaggedEnum {
+    pub kind_name: String,
+    ///the Rust code of the plain enum
+    pub kind_enum: String,
+    ///the factories, in the order of the variants, followed by `kind` and the getters
+    pub methods: Vec<TaggedMethod>,
+}
+
+pub struct TaggedMethod {
+    pub name: String,
+    pub docs: Vec<String>,
+    ///the Rust code of the method of the companion file
+    pub shim: String,
+}
+
+impl TaggedEnum {
+    pub fn new(item: &ItemEnum) -> TaggedEnum {
+        let enum_name = item.ident.to_string();
+        let kind_name = format!("{}{}", enum_name, KIND_ENUM_SUFFIX);
+        let mut factories = vec![];
+        let mut getters = vec![];
+        let mut kind_arms = String::new();
+        let mut kind_variants = String::new();
+        for variant in &item.variants {
+            let variant_name = variant.ident.to_string();
+            let method_name = variant_name.to_snake_case();
+            let (params, construct, rest) = match &variant.fields {
+                Fields::Named(fields) => {
+                    let names = fields
+                        .named
+                        .iter()
+                        .map(|it| it.ident.as_ref().unwrap().to_string())
+                        .collect::<Vec<_>>();
+                    let construct = format!(" {{ {} }}", names.join(", "));
+                    (names, construct, " { .. }")
+                }
+                Fields::Unnamed(fields) => {
+                    let names = (0..fields.unnamed.len())
+                        .map(|it| format!("a{}", it))
+                        .collect::<Vec<_>>();
+                    let construct = format!("({})", names.join(", "));
+                    (names, construct, "(..)")
+                }
+                Fields::Unit => (vec![], String::new(), ""),
+            };
+            factories.push(TaggedMethod {
+                name: method_name.clone(),
+                docs: item_docs(&variant.attrs),
+                shim: format!(
+                    "
+    pub fn {}{}({}) -> {} {{
+        {}::{}{}
+    }}
+",
+                    TAGGED_ENUM_PREFIX,
+                    method_name,
+                    params
+                        .iter()
+                        .zip(variant.fields.iter())
+                        .map(|(name, field)| format!("{}: {}", name, field.ty.to_token_stream()))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    enum_name,
+                    enum_name,
+                    variant_name,
+                    construct
+                ),
+            });
+            kind_arms.push_str(&format!(
+                "
+            {}::{}{} => {}::{},",
+                enum_name, variant_name, rest, kind_name, variant_name
+            ));
+            kind_variants.push_str(&format!("\n    {},", variant_name));
+            for (index, field) in variant.fields.iter().enumerate() {
+                let (field_name, pattern) = match &field.ident {
+                    Some(ident) => (ident.to_string(), format!(" {{ {}: val, .. }}", ident)),
+                    None => (
+                        index.to_string(),
+                        format!("({}val, ..)", "_, ".repeat(index)),
+                    ),
+                };
+                let getter_name = format!("get_{}_{}", method_name, field_name);
+                getters.push(TaggedMethod {
+                    name: getter_name.clone(),
+                    docs: item_docs(&field.attrs),
+                    shim: format!(
+                        "
+    pub fn {prefix}{getter}(&self) -> Result<{ty}, String> {{
+        match self {{
+            {enum_name}::{variant}{pattern} => Ok(Clone::clone(val)),{other}
+        }}
+    }}
+",
+                        prefix = TAGGED_ENUM_PREFIX,
+                        getter = getter_name,
+                        ty = field.ty.to_token_stream(),
+                        enum_name = enum_name,
+                        variant = variant_name,
+                        pattern = pattern,
+                        other = if item.variants.len() > 1 {
+                            format!(
+                                "
+            _ => Err(String::from(\"the variant isn't {}::{}\")),",
+                                enum_name, variant_name
+                            )
+                        } else {
+                            String::new()
+                        }
+                    ),
+                })
+            }
+        }
+        let kind = TaggedMethod {
+            name: String::from("kind"),
+            docs: vec![String::from("# [doc = \" The variant of this value\"]")],
+            shim: format!(
+                "
+    pub fn {}kind(&self) -> {} {{
+        match self {{{}
+        }}
+    }}
+",
+                TAGGED_ENUM_PREFIX, kind_name, kind_arms
+            ),
+        };
+        let kind_enum = format!(
+            "
+///Variants of [`{}`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum {} {{{}
+}}
+",
+            enum_name, kind_name, kind_variants
+        );
+        factories.push(kind);
+        factories.append(&mut getters);
+        TaggedEnum {
+            kind_name,
+            kind_enum,
+            methods: factories,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tagged_enums::TaggedEnum;
+
+    #[test]
+    fn tagged_enum() {
+        let item =
+            syn::parse_str("enum Shape { Circle(f64), Rect { w: f64, h: f64 }, Empty }").unwrap();
+        let tagged = TaggedEnum::new(&item);
+        assert_eq!(tagged.kind_name, "ShapeKind");
+        assert!(tagged
+            .kind_enum
+            .contains("pub enum ShapeKind {\n    Circle,\n    Rect,\n    Empty,\n}"));
+        let names = tagged
+            .methods
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                "circle",
+                "rect",
+                "empty",
+                "kind",
+                "get_circle_0",
+                "get_rect_w",
+                "get_rect_h"
+            ]
+        );
+        assert_eq!(
+            tagged.methods[1].shim,
+            "
+    pub fn rifgen_tagged_rect(w: f64, h: f64) -> Shape {
+        Shape::Rect { w, h }
+    }
+"
+        );
+        assert_eq!(
+            tagged.methods[3].shim,
+            "
+    pub fn rifgen_tagged_kind(&self) -> ShapeKind {
+        match self {
+            Shape::Circle(..) => ShapeKind::Circle,
+            Shape::Rect { .. } => ShapeKind::Rect,
+            Shape::Empty => ShapeKind::Empty,
+        }
+    }
+"
+        );
+        assert_eq!(
+            tagged.methods[4].shim,
+            "
+    pub fn rifgen_tagged_get_circle_0(&self) -> Result<f64, String> {
+        match self {
+            Shape::Circle(val, ..) => Ok(Clone::clone(val)),
+            _ => Err(String::from(\"the variant isn't Shape::Circle\")),
+        }
+    }
+"
+        );
+        assert!(tagged.methods[6]
+            .shim
+            .contains("Shape::Rect { h: val, .. } => Ok(Clone::clone(val)),"));
+    }
+}
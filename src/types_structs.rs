@@ -1,11 +1,25 @@
-use crate::enums::{Delimiters, NewLineState, Types};
+use crate::annotations::ForeignAnnotation;
+use crate::async_fns::ASYNC_WRAPPER_PREFIX;
+use crate::closures::CLOSURE_WRAPPER_PREFIX;
+use crate::companion::{
+    debug_string_shim, enum_value_shims, hash_code_shim, shared_constructor_shim, CompanionFile,
+    DEBUG_STRING_SHIM, FROM_VALUE_SHIM, HASH_CODE_SHIM, SHARED_CONSTRUCTOR_PREFIX,
+    VALUES_CLASS_SUFFIX, VALUE_SHIM,
+};
+use crate::consts::CONST_GETTER_PREFIX;
+use crate::docs::translate_docs;
+use crate::enums::{Delimiters, NewLineState, SharedOwnership, Types};
 use crate::generator_lib::{F_CALLBACK, F_CLASS, F_ENUM};
+use crate::tagged_enums::TAGGED_ENUM_PREFIX;
 use crate::text_formatter::StringFormatter;
-use crate::TypeCases;
+use crate::{Language, TypeCases};
 use derive_new::new;
 use inflector::Inflector;
+use std::collections::HashSet;
 use std::iter::Chain;
 use std::slice::Iter;
+use syn::__private::ToTokens;
+use syn::{FnArg, ReturnType, Signature, Type};
 
 //todo: avoid static mut
 pub(crate) static mut TYPE_CASE: TypeCases = TypeCases::Default;
@@ -21,18 +35,34 @@ pub struct ItemInfo {
     ///method name to use with only methods
     ///No enums included
     pub method_info: Option<MethodInfo>,
+    ///Java annotations of the method
+    #[new(default)]
+    pub annotations: Vec<String>,
+    ///Value of enum variants which is known when it's an integer literal or follows one
+    #[new(default)]
+    pub discriminant: Option<i128>,
 }
 #[derive(Debug, new)]
 pub struct MethodInfo {
     name: String,
     types_in_method: Vec<String>,
     return_types: Vec<String>,
+    /// the signature as it is in the source code
+    rust_signature: Signature,
 }
 
 impl MethodInfo {
     pub fn all_types(&self) -> Chain<Iter<'_, String>, Iter<'_, String>> {
         self.types_in_method.iter().chain(self.return_types.iter())
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn rust_signature(&self) -> &Signature {
+        &self.rust_signature
+    }
 }
 
 impl ItemInfo {
@@ -44,12 +74,18 @@ impl ItemInfo {
         is_constructor: bool,
         types_in_method: Vec<String>,
         return_types: Vec<String>,
+        rust_signature: Signature,
     ) -> ItemInfo {
         ItemInfo::new(
             docs,
             signature,
             is_constructor,
-            Some(MethodInfo::new(method_name, types_in_method, return_types)),
+            Some(MethodInfo::new(
+                method_name,
+                types_in_method,
+                return_types,
+                rust_signature,
+            )),
         )
     }
 
@@ -58,6 +94,73 @@ impl ItemInfo {
         ItemInfo::new(docs, signature, false, None)
     }
 }
+///What a constructor of a class returns
+enum ConstructorKind {
+    ///The class itself ie `Foo` or `Self`
+    Plain,
+    ///The class wrapped in the shared pointer of the class
+    Shared,
+    Other(String),
+}
+
+impl ConstructorKind {
+    fn new(class_name: &str, shared: Option<SharedOwnership>, signature: &Signature) -> Self {
+        let return_type = match &signature.output {
+            ReturnType::Type(_, val) => val,
+            ReturnType::Default => return ConstructorKind::Other(String::from("()")),
+        };
+        if let Type::Path(val) = &**return_type {
+            if val.qself.is_none() && (val.path.is_ident(class_name) || val.path.is_ident("Self")) {
+                return ConstructorKind::Plain;
+            }
+        }
+        let return_type = return_type.to_token_stream().to_string().replace(' ', "");
+        match shared {
+            Some(shared) if shared.matches(class_name, &return_type) => ConstructorKind::Shared,
+            _ => ConstructorKind::Other(return_type),
+        }
+    }
+}
+
+///Arguments of the constructor renamed to `a0`, `a1`, ... since patterns
+/// can't be used to call the constructor
+fn constructor_args(signature: &Signature) -> Vec<(String, String)> {
+    signature
+        .inputs
+        .iter()
+        .filter_map(|it| match it {
+            FnArg::Receiver(_) => None,
+            FnArg::Typed(val) => Some(val.ty.to_token_stream().to_string()),
+        })
+        .enumerate()
+        .map(|(index, ty)| (format!("a{}", index), ty))
+        .collect()
+}
+
+///Whether the method is a wrapper calling an async method or a method taking closures,
+/// the getter of a const or a method of a tagged enum
+fn is_wrapper(signature: &str) -> bool {
+    [
+        ASYNC_WRAPPER_PREFIX,
+        CLOSURE_WRAPPER_PREFIX,
+        CONST_GETTER_PREFIX,
+        TAGGED_ENUM_PREFIX,
+    ]
+    .iter()
+    .any(|it| signature.starts_with(it))
+}
+
+///Attributes of the interface file for the annotations. Only Java supports them
+fn annotation_attributes(annotations: &[String], language: Language) -> Vec<String> {
+    match language {
+        Language::Java => annotations
+            .iter()
+            .map(|it| ForeignAnnotation::new(it).attribute().to_string())
+            .collect(),
+        Language::Cpp => Vec::new(),
+    }
+}
+
 ///Convenient macro to add the doc comments
 #[macro_export]
 #[doc(hidden)]
@@ -86,23 +189,132 @@ macro_rules! gen_structs {
                 pub docs: Vec<String>,
                 /// the methods or variants with this type
                 pub extras: Vec<ItemInfo>,
-                /// annotate with #[derive(Clone)]
-                pub is_clone: bool
+                /// traits derived or implemented by this type
+                #[new(default)]
+                pub traits: HashSet<String>,
+                /// export equals, hashCode and toString from the implemented traits
+                #[new(default)]
+                pub object_methods: bool,
+                /// pointer wrapping the instances created by the constructors
+                #[new(default)]
+                pub shared: Option<SharedOwnership>,
+                /// `Send` and `Sync` supertraits of callbacks
+                #[new(default)]
+                pub supertraits: Vec<String>,
+                /// Java annotations of the class
+                #[new(default)]
+                pub annotations: Vec<String>,
+                /// Rust code of the wrappers calling async methods and methods taking closures
+                #[new(default)]
+                pub wrappers: Vec<String>,
+                /// Rust code of the types generated by rifgen ie callbacks
+                #[new(default)]
+                pub definition: Option<String>,
+                /// integer type of the values of enums with discriminants or an integer `#[repr]`
+                #[new(default)]
+                pub value_type: Option<String>,
+                /// signature of the constructor flapigen requires for classes created by static methods
+                #[new(default)]
+                pub private_constructor: Option<String>,
             }
 
             impl $name {
-                pub fn generate_interface(&mut self) -> String {
+                pub fn generate_interface(&mut self, language: Language) -> String {
                     let mut formatter = StringFormatter::new(String::with_capacity(1024), 0);
                     match self.type_ {
-                        Types::Struct => self.format_struct(&mut formatter),
+                        Types::Struct => self.format_struct(&mut formatter, language),
                         Types::Trait => self.format_trait(&mut formatter),
-                        Types::Enum => {self.format_enum(&mut formatter)}
+                        Types::Enum => self.format_enum(&mut formatter, language),
                     }
                     formatter.close_all_delimiters();
                     formatter.string_container
                 }
 
-                fn format_struct(&mut self, formatter: &mut StringFormatter) {
+                ///Converts the doc comments of the type and its methods or variants to Javadoc or Doxygen
+                pub fn translate_docs(&mut self, language: Language) {
+                    self.docs = translate_docs(&self.docs, language);
+                    for extra in &mut self.extras {
+                        extra.docs = translate_docs(&extra.docs, language);
+                    }
+                }
+
+                ///Adds the Rust helper methods needed by the interface of this type
+                pub fn companion_code(&self, companion: &mut CompanionFile) {
+                    let mut methods = vec![];
+                    if self.object_methods {
+                        if self.traits.contains("Hash") {
+                            methods.push(hash_code_shim());
+                        }
+                        if !self.traits.contains("Display") && self.traits.contains("Debug") {
+                            methods.push(debug_string_shim());
+                        }
+                    }
+                    if let Some(shared) = self.shared {
+                        for constructor in self.extras.iter().filter(|it| it.is_constructor) {
+                            let method_info = constructor.method_info.as_ref().unwrap();
+                            let signature = &method_info.rust_signature;
+                            if let ConstructorKind::Plain = ConstructorKind::new(&self.name, self.shared, signature) {
+                                methods.push(shared_constructor_shim(
+                                    shared,
+                                    &self.name,
+                                    &method_info.name,
+                                    &signature.generics.to_token_stream().to_string(),
+                                    &constructor_args(signature),
+                                ));
+                            }
+                        }
+                    }
+                    if let Some(value_type) = &self.value_type {
+                        let variants = self.extras.iter().map(|it| it.signature.as_str()).collect::<Vec<_>>();
+                        methods.append(&mut enum_value_shims(&self.name, value_type, &variants));
+                    }
+                    methods.extend(self.wrappers.iter().cloned());
+                    companion.add_impl(&self.name, methods);
+                    if let Some(definition) = &self.definition {
+                        companion.add_code(definition);
+                    }
+                }
+
+                ///Makes sure all the constructors return the same type as required by flapigen.
+                /// When the class is shared, constructors returning the class itself are wrapped
+                fn constructor_kinds(&self, constructors: &[ItemInfo]) -> Vec<ConstructorKind> {
+                    let kinds = constructors
+                        .iter()
+                        .map(|it| ConstructorKind::new(
+                            &self.name,
+                            self.shared,
+                            &it.method_info.as_ref().unwrap().rust_signature,
+                        ))
+                        .collect::<Vec<_>>();
+                    let mut first_return_type: Option<&str> = None;
+                    for (constructor, kind) in constructors.iter().zip(kinds.iter()) {
+                        let method_name = &constructor.method_info.as_ref().unwrap().name;
+                        match (kind, self.shared) {
+                            (ConstructorKind::Other(return_type), Some(shared)) => panic!(
+                                "Constructor {}::{} returns {} but {} is shared, so it should return {} or {}",
+                                self.name, method_name, return_type, self.name, self.name, shared.wrap_type(&self.name)
+                            ),
+                            (_, Some(_)) => {}
+                            (_, None) => {
+                                let return_type = match kind {
+                                    ConstructorKind::Other(return_type) => return_type.as_str(),
+                                    _ => self.name.as_str(),
+                                };
+                                match first_return_type {
+                                    Some(first) if first != return_type => panic!(
+                                        "Constructors of {} should all return the same type. {}::{} returns {} instead of {}",
+                                        self.name, self.name, method_name, return_type, first
+                                    ),
+                                    Some(_) => {}
+                                    None => first_return_type = Some(return_type),
+                                }
+                            }
+                        }
+                    }
+                    kinds
+                }
+
+                fn format_struct(&mut self, formatter: &mut StringFormatter, language: Language) {
                     //Case where the struct has constructors
                     let constructors = {
                          let mut result = vec![];
@@ -123,8 +335,16 @@ macro_rules! gen_structs {
                         Delimiters::Parenthesis,
                         NewLineState::ShiftRight,
                     );
-                    if self.is_clone {
-                        formatter.add_text_and_then_line(vec!["#[derive(Clone)]"],NewLineState::Current);
+                    //the annotations are added by callbacks registered for these derives
+                    let mut derives = annotation_attributes(&self.annotations, language);
+                    if self.traits.contains("Clone") {
+                        derives.insert(0, String::from("Clone"));
+                    }
+                    if !derives.is_empty() {
+                        formatter.add_text_and_then_line(
+                            vec!["#[derive(", &derives.join(", "), ")]"],
+                            NewLineState::Current,
+                        );
                     }
                     //Add the doc comment associated with this struct
                     add_doc!(self, formatter);
@@ -136,25 +356,58 @@ macro_rules! gen_structs {
 
                     if any_is_constructor {
                         formatter.add_text_and_colon(vec!["self_type ",&self.name]);
-                        for constructor in constructors {
+                        let kinds = self.constructor_kinds(&constructors);
+                        for (constructor, kind) in constructors.iter().zip(kinds) {
                             //add doc comment
-                            formatter.add_text_and_then_line(
-                                constructor.docs.iter().map(|it| it.as_str()).collect(),
-                                NewLineState::Current,
-                            );
-                            formatter.add_text_and_colon(vec![
-                                "constructor ",
-                                &self.name,
-                                "::",
-                                &constructor.signature,
-                            ])
+                            add_doc!(constructor, formatter);
+                            match (kind, self.shared) {
+                                (ConstructorKind::Plain, Some(shared)) => {
+                                    //call the wrapper in the companion file instead
+                                    let method_info = constructor.method_info.as_ref().unwrap();
+                                    let args = constructor_args(&method_info.rust_signature)
+                                        .into_iter()
+                                        .map(|(name, ty)| format!("{}: {}", name, ty))
+                                        .collect::<Vec<_>>()
+                                        .join(", ");
+                                    formatter.add_text_and_colon(vec![
+                                        "constructor ",
+                                        &self.name,
+                                        "::",
+                                        SHARED_CONSTRUCTOR_PREFIX,
+                                        &method_info.name,
+                                        "(",
+                                        &args,
+                                        ")->",
+                                        &shared.wrap_type(&self.name),
+                                    ])
+                                }
+                                _ => formatter.add_text_and_colon(vec![
+                                    "constructor ",
+                                    &self.name,
+                                    "::",
+                                    &constructor.signature,
+                                ]),
+                            }
                         }
+                    } else if let Some(constructor) = &self.private_constructor {
+                        formatter.add_text_and_colon(vec!["self_type ", &self.name]);
+                        formatter.add_text_and_colon(vec!["private constructor ", &self.name, "::", constructor]);
                     }
 
                     for extra in &self.extras {
                         add_doc!(&extra, formatter);
+                        for attribute in annotation_attributes(&extra.annotations, language) {
+                            formatter.add_text_and_then_line(
+                                vec!["#[", &attribute, "]"],
+                                NewLineState::Current,
+                            );
+                        }
                         let alias = unsafe {
                             match TYPE_CASE {
+                                //the wrappers keep the name of the method
+                                TypeCases::Default if is_wrapper(&extra.signature) => {
+                                    (&extra.method_info).as_ref().unwrap().name.to_string()
+                                }
                                 TypeCases::Default => String::new(),
                                 TypeCases::CamelCase => (&extra.method_info).as_ref().unwrap().name.to_camel_case(),
                                 TypeCases::SnakeCase => (&extra.method_info).as_ref().unwrap().name.to_snake_case(),
@@ -167,6 +420,56 @@ macro_rules! gen_structs {
                         };
                         formatter.add_text_and_colon(vec!["fn ", &self.name, "::", &extra.signature, &alias])
                     }
+                    if self.object_methods {
+                        self.format_object_methods(formatter, language);
+                    }
+                }
+
+                ///`equals`, `hashCode` and `toString` from `PartialEq`, `Hash` and `Display` or `Debug`.\
+                /// In Java `eq` is `contentEquals` since an `equals` taking the class would only overload
+                /// `equals(Object)`, which is overridden in the class body instead. Both `equals(Object)`
+                /// and `hashCode` need `PartialEq` and `Hash` so equal objects have the same hash
+                fn format_object_methods(&self, formatter: &mut StringFormatter, language: Language) {
+                    let (equals, hash_code, to_string) = match language {
+                        Language::Java => ("contentEquals", "hashCode", "toString"),
+                        Language::Cpp => ("equals", "hash_code", "to_string"),
+                    };
+                    let partial_eq = self.traits.contains("PartialEq");
+                    let hash = self.traits.contains("Hash");
+                    if partial_eq {
+                        formatter.add_text_and_colon(vec![
+                            "fn ", &self.name, "::eq(&self, other: &", &self.name, ")->bool; alias ", equals,
+                        ]);
+                    }
+                    if partial_eq && hash && matches!(language, Language::Java) {
+                        let equals_override = format!(
+                            "foreign_code r#\"
+    @Override
+    public boolean equals(Object other) {{
+        return other instanceof {name} && contentEquals(({name}) other);
+    }}
+\"#",
+                            name = self.name
+                        );
+                        formatter.add_text_and_colon(vec![&equals_override]);
+                    }
+                    if hash && (partial_eq || matches!(language, Language::Cpp)) {
+                        formatter.add_text_and_colon(vec![
+                            "fn ", &self.name, "::", HASH_CODE_SHIM, "(&self)->i32; alias ", hash_code,
+                        ]);
+                    }
+                    let to_string_method = if self.traits.contains("Display") {
+                        Some("to_string")
+                    } else if self.traits.contains("Debug") {
+                        Some(DEBUG_STRING_SHIM)
+                    } else {
+                        None
+                    };
+                    if let Some(method) = to_string_method {
+                        formatter.add_text_and_colon(vec![
+                            "fn ", &self.name, "::", method, "(&self)->String; alias ", to_string,
+                        ]);
+                    }
                 }
 
                 fn format_trait(&mut self, formatter: &mut StringFormatter) {
@@ -182,7 +485,12 @@ macro_rules! gen_structs {
                         Delimiters::Bracket,
                         NewLineState::ShiftRight,
                     );
-                    formatter.add_text_and_colon(vec!["self_type ",&self.name]);
+                    let mut self_type = vec!["self_type ", &self.name];
+                    for supertrait in &self.supertraits {
+                        self_type.push(" + ");
+                        self_type.push(supertrait);
+                    }
+                    formatter.add_text_and_colon(self_type);
                     for extra in &self.extras {
                         add_doc!(extra, formatter);
                         let mut name = extra.method_info.as_ref().unwrap().name.to_string();
@@ -197,7 +505,7 @@ macro_rules! gen_structs {
                     }
                 }
 
-                fn format_enum(&mut self, formatter: &mut StringFormatter) {
+                fn format_enum(&mut self, formatter: &mut StringFormatter, language: Language) {
                     formatter.add_text_delimiter_then_line(
                         vec![F_ENUM],
                         Delimiters::Parenthesis,
@@ -211,6 +519,16 @@ macro_rules! gen_structs {
                     );
                     for extra in &self.extras {
                         add_doc!(extra, formatter);
+                        //the ordinal of the foreign enum isn't the value
+                        if let Some(value) = extra.discriminant {
+                            if !extra.docs.is_empty() {
+                                formatter.add_text_and_then_line(vec!["# [doc = \"\"]"], NewLineState::Current);
+                            }
+                            formatter.add_text_and_then_line(
+                                vec!["# [doc = \" Value: ", &value.to_string(), "\"]"],
+                                NewLineState::Current,
+                            );
+                        }
                         formatter.add_text_and_comma(vec![
                             &extra.signature,
                             " = ",
@@ -219,6 +537,42 @@ macro_rules! gen_structs {
                             &extra.signature,
                         ])
                     }
+                    if let Some(value_type) = &self.value_type {
+                        formatter.close_all_delimiters();
+                        self.format_values_class(formatter, value_type, language);
+                    }
+                }
+
+                ///Static methods converting the variants to their values and back since
+                /// flapigen enums can't have methods
+                fn format_values_class(&self, formatter: &mut StringFormatter, value_type: &str, language: Language) {
+                    let (value, from_value, link) = match language {
+                        Language::Java => ("value", "fromValue", format!("{{@link {}}}", self.name)),
+                        Language::Cpp => ("value", "from_value", self.name.to_string()),
+                    };
+                    let class_name = format!("{}{}", self.name, VALUES_CLASS_SUFFIX);
+                    formatter.add_text_delimiter_then_line(
+                        vec![F_CLASS],
+                        Delimiters::Parenthesis,
+                        NewLineState::ShiftRight,
+                    );
+                    formatter.add_text_and_then_line(
+                        vec!["# [doc = \" Values of the variants of ", &link, "\"]"],
+                        NewLineState::Current,
+                    );
+                    formatter.add_text_delimiter_then_line(
+                        vec!["class ", &class_name],
+                        Delimiters::Bracket,
+                        NewLineState::ShiftRight,
+                    );
+                    formatter.add_text_and_colon(vec![
+                        "fn ", &self.name, "::", VALUE_SHIM, "(variant: ", &self.name, ")->", value_type,
+                        "; alias ", value,
+                    ]);
+                    formatter.add_text_and_colon(vec![
+                        "fn ", &self.name, "::", FROM_VALUE_SHIM, "(value: ", value_type, ")->Result<",
+                        &self.name, ", String>; alias ", from_value,
+                    ]);
                 }
             }
         )*
@@ -437,3 +791,202 @@ mod tests {
     }
 }
 */
+
+#[cfg(test)]
+mod tests {
+    use crate::enums::SharedOwnership;
+    use crate::types_structs::ConstructorKind;
+    use crate::{Generator, Language, TypeCases};
+
+    ///Generates the interface and the companion file of `code` for Java
+    fn generate(test_name: &str, code: &str) -> (String, String) {
+        let folder = std::env::temp_dir().join(test_name);
+        let source = folder.join("src");
+        std::fs::create_dir_all(&source).unwrap();
+        std::fs::write(source.join("lib.rs"), code).unwrap();
+        Generator::new(TypeCases::CamelCase, Language::Java, vec![&source])
+            .generate_interface(folder.join("glue.in"));
+        let interface = std::fs::read_to_string(folder.join("glue.in")).unwrap();
+        let companion = std::fs::read_to_string(folder.join("glue_shims.rs")).unwrap();
+        std::fs::remove_dir_all(&folder).unwrap();
+        (interface, companion)
+    }
+
+    fn kind(shared: Option<SharedOwnership>, signature: &str) -> ConstructorKind {
+        ConstructorKind::new("Counter", shared, &syn::parse_str(signature).unwrap())
+    }
+
+    #[test]
+    fn constructor_kinds() {
+        for shared in [
+            None,
+            Some(SharedOwnership::ArcMutex),
+            Some(SharedOwnership::RcRefCell),
+        ] {
+            assert!(matches!(
+                kind(shared, "fn new() -> Counter"),
+                ConstructorKind::Plain
+            ));
+            assert!(matches!(
+                kind(shared, "fn new() -> Self"),
+                ConstructorKind::Plain
+            ));
+            assert!(matches!(
+                kind(shared, "fn new()"),
+                ConstructorKind::Other(val) if val == "()"
+            ));
+        }
+        let shared = Some(SharedOwnership::ArcMutex);
+        assert!(matches!(
+            kind(shared, "fn new() -> Arc<Mutex<Counter>>"),
+            ConstructorKind::Shared
+        ));
+        assert!(matches!(
+            kind(
+                shared,
+                "fn new() -> std::sync::Arc<std::sync::Mutex<Counter>>"
+            ),
+            ConstructorKind::Shared
+        ));
+        assert!(matches!(
+            kind(shared, "fn new() -> Rc<RefCell<Counter>>"),
+            ConstructorKind::Other(val) if val == "Rc<RefCell<Counter>>"
+        ));
+        let shared = Some(SharedOwnership::RcRefCell);
+        assert!(matches!(
+            kind(shared, "fn new() -> Rc<RefCell<Counter>>"),
+            ConstructorKind::Shared
+        ));
+        assert!(matches!(
+            kind(
+                shared,
+                "fn new() -> std::rc::Rc<std::cell::RefCell<Counter>>"
+            ),
+            ConstructorKind::Shared
+        ));
+        assert!(matches!(
+            kind(None, "fn new() -> Rc<RefCell<Counter>>"),
+            ConstructorKind::Other(val) if val == "Rc<RefCell<Counter>>"
+        ));
+    }
+
+    #[test]
+    fn arc_mutex_class() {
+        let (interface, companion) = generate(
+            "rifgen_arc_mutex_test",
+            "
+            #[generate_interface_doc(shared = \"arc_mutex\")]
+            pub struct Counter { count: u32 }
+
+            impl Counter {
+                #[generate_interface(constructor)]
+                pub fn new(start: u32) -> Counter { Counter { count: start } }
+
+                #[generate_interface(constructor)]
+                pub fn shared() -> Arc<Mutex<Counter>> { todo!() }
+
+                #[generate_interface]
+                pub fn increment(&mut self) { self.count += 1 }
+            }
+            ",
+        );
+        assert!(interface.contains("use std::sync::{Arc, Mutex};\n"));
+        assert!(interface.contains(
+            "
+		self_type Counter;
+		constructor Counter::rifgen_shared_new(a0: u32)->Arc<Mutex<Counter>>;
+		constructor Counter::shared()->Arc<Mutex<Counter>>;
+		fn Counter::increment(& mut self); alias increment;
+"
+        ));
+        assert!(companion.contains(
+            "
+impl Counter {
+    pub fn rifgen_shared_new(a0: u32) -> std::sync::Arc<std::sync::Mutex<Counter>> {
+        std::sync::Arc::new(std::sync::Mutex::new(Counter::new(a0)))
+    }
+}
+"
+        ));
+        //constructors already returning the shared pointer don't need a shim
+        assert!(!companion.contains("rifgen_shared_shared"));
+    }
+
+    #[test]
+    fn rc_refcell_class() {
+        let (interface, companion) = generate(
+            "rifgen_rc_refcell_test",
+            "
+            #[generate_interface_doc(shared = \"rc_refcell\")]
+            pub struct Counter { count: u32 }
+
+            impl Counter {
+                #[generate_interface(constructor)]
+                pub fn new() -> Self { Counter { count: 0 } }
+
+                #[generate_interface]
+                pub fn count(&self) -> u32 { self.count }
+            }
+            ",
+        );
+        assert!(interface.contains("use std::cell::RefCell;\nuse std::rc::Rc;\n"));
+        assert!(interface.contains(
+            "
+		self_type Counter;
+		constructor Counter::rifgen_shared_new()->Rc<RefCell<Counter>>;
+		fn Counter::count(& self)->u32; alias count;
+"
+        ));
+        assert!(companion.contains(
+            "
+    pub fn rifgen_shared_new() -> std::rc::Rc<std::cell::RefCell<Counter>> {
+        std::rc::Rc::new(std::cell::RefCell::new(Counter::new()))
+    }
+"
+        ));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Constructor Counter::shared returns Arc<Mutex<Counter>> but Counter is shared, so it should return Counter or Rc<RefCell<Counter>>"
+    )]
+    fn mismatched_shared_constructor() {
+        generate(
+            "rifgen_mismatched_shared_test",
+            "
+            #[generate_interface_doc(shared = \"rc_refcell\")]
+            pub struct Counter { count: u32 }
+
+            impl Counter {
+                #[generate_interface(constructor)]
+                pub fn new() -> Counter { Counter { count: 0 } }
+
+                #[generate_interface(constructor)]
+                pub fn shared() -> Arc<Mutex<Counter>> { todo!() }
+            }
+            ",
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Constructors of Counter should all return the same type. Counter::boxed returns Box<Counter> instead of Counter"
+    )]
+    fn mismatched_constructor() {
+        generate(
+            "rifgen_mismatched_constructor_test",
+            "
+            #[generate_interface_doc]
+            pub struct Counter { count: u32 }
+
+            impl Counter {
+                #[generate_interface(constructor)]
+                pub fn new() -> Counter { Counter { count: 0 } }
+
+                #[generate_interface(constructor)]
+                pub fn boxed() -> Box<Counter> { todo!() }
+            }
+            ",
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use crate::VisibilityPolicy;
+use std::path::Path;
+use syn::Visibility;
+
+///Finds the exported methods which can't be called from the module the interface file is
+/// included in (ie private methods in another module)
+pub struct VisibilityCheck {
+    policy: VisibilityPolicy,
+    ///module including the interface file ie `crate::ffi` as `["crate", "ffi"]`
+    glue_module: Vec<String>,
+    ///module of the file being read
+    module: Vec<String>,
+    ///the affected methods
+    report: Vec<String>,
+}
+
+impl VisibilityCheck {
+    pub fn new(policy: VisibilityPolicy, glue_module: Option<&str>) -> VisibilityCheck {
+        let mut glue_module = glue_module
+            .unwrap_or("crate")
+            .split("::")
+            .map(|it| it.trim().to_string())
+            .filter(|it| !it.is_empty())
+            .collect::<Vec<_>>();
+        if glue_module.first().map(String::as_str) != Some("crate") {
+            glue_module.insert(0, String::from("crate"))
+        }
+        VisibilityCheck {
+            policy,
+            glue_module,
+            module: vec![String::from("crate")],
+            report: Vec::new(),
+        }
+    }
+
+    ///Sets the module of the methods checked next from the path of the file
+    pub fn set_file(&mut self, source_folder: &Path, file: &Path) {
+        self.module = module_path(source_folder, file)
+    }
+
+    ///Records the method if the interface file can't call it.
+    /// Returns `false` if the method shouldn't be exported
+    pub fn check(&mut self, type_name: &str, method: &syn::ImplItemMethod) -> bool {
+        self.check_item(type_name, &method.vis, &method.sig.ident)
+    }
+
+    ///Same as [`VisibilityCheck::check`] for other items such as consts
+    pub fn check_item(
+        &mut self,
+        type_name: &str,
+        vis: &syn::Visibility,
+        name: &syn::Ident,
+    ) -> bool {
+        if is_visible(vis, &self.module, &self.glue_module) {
+            return true;
+        }
+        self.report.push(format!(
+            "{}::{} in {}",
+            type_name,
+            name,
+            self.module.join("::")
+        ));
+        self.policy != VisibilityPolicy::Skip
+    }
+
+    ///Reports the methods found according to the policy
+    pub fn finish(self) {
+        if self.report.is_empty() {
+            return;
+        }
+        let glue_module = self.glue_module.join("::");
+        match self.policy {
+            VisibilityPolicy::Warn => self.report.iter().for_each(|it| {
+                println!(
+                    "cargo:warning={} is exported but isn't visible from {}",
+                    it, glue_module
+                )
+            }),
+            VisibilityPolicy::Skip => self.report.iter().for_each(|it| {
+                println!(
+                    "cargo:warning=Skipped {} since it isn't visible from {}",
+                    it, glue_module
+                )
+            }),
+            VisibilityPolicy::Error => panic!(
+                "The following exported methods aren't visible from {}:\n{}",
+                glue_module,
+                self.report.join("\n")
+            ),
+        }
+    }
+}
+
+///Path of the module of a file relative to the source folder
+/// ie `["crate", "a", "b"]` for `a/b.rs` or `a/b/mod.rs`
+fn module_path(source_folder: &Path, file: &Path) -> Vec<String> {
+    let mut module = vec![String::from("crate")];
+    let relative = file.strip_prefix(source_folder).unwrap_or(file);
+    let mut components = relative
+        .with_extension("")
+        .iter()
+        .map(|it| it.to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    match components.last().map(String::as_str) {
+        Some("mod") => {
+            components.pop();
+        }
+        Some("lib" | "main") if components.len() == 1 => {
+            components.pop();
+        }
+        _ => {}
+    }
+    module.append(&mut components);
+    module
+}
+
+///Checks if an item with the visibility `vis` declared in `module` can be used in `from`
+fn is_visible(vis: &Visibility, module: &[String], from: &[String]) -> bool {
+    let visible_in = match vis {
+        Visibility::Public(_) | Visibility::Crate(_) => return true,
+        Visibility::Inherited => module.to_vec(),
+        Visibility::Restricted(val) => {
+            let mut path = module.to_vec();
+            for (index, segment) in val.path.segments.iter().enumerate() {
+                match segment.ident.to_string().as_str() {
+                    "crate" if index == 0 => path = vec![String::from("crate")],
+                    "self" if index == 0 => {}
+                    "super" => {
+                        path.pop();
+                    }
+                    //absolute paths such as `in crate::a` start with `crate`
+                    segment => path.push(segment.to_string()),
+                }
+            }
+            path
+        }
+    };
+    from.starts_with(&visible_in)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visibility::{is_visible, module_path};
+    use std::path::Path;
+
+    #[test]
+    fn visibility() {
+        let module = module_path(Path::new("src"), Path::new("src/a/b.rs"));
+        assert_eq!(module, vec!["crate", "a", "b"]);
+        assert_eq!(
+            module_path(Path::new("src"), Path::new("src/a/mod.rs")),
+            vec!["crate", "a"]
+        );
+        assert_eq!(
+            module_path(Path::new("src"), Path::new("src/lib.rs")),
+            vec!["crate"]
+        );
+        let glue = ["crate".to_string(), "a".to_string()];
+        let visible = |vis: &str| is_visible(&syn::parse_str(vis).unwrap(), &module, &glue);
+        assert!(visible("pub"));
+        assert!(visible("pub(crate)"));
+        assert!(visible("pub(super)"));
+        assert!(visible("pub(in crate::a)"));
+        assert!(!visible(""));
+        assert!(!visible("pub(self)"));
+        assert!(!visible("pub(in crate::a::b)"));
+    }
+}